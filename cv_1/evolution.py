@@ -0,0 +1,40 @@
+import pandas as pd
+import matplotlib.pyplot as plt
+
+
+METRICS = [
+    ('nodes', 'Nodes'),
+    ('edges', 'Edges'),
+    ('new_nodes', 'New nodes'),
+    ('new_edges', 'New edges'),
+    ('density', 'Density'),
+    ('avg_degree', 'Average degree'),
+    ('avg_strength', 'Average strength'),
+    ('clustering', 'Clustering coefficient'),
+    ('giant_component', 'Giant component size'),
+    ('avg_path_length', 'Average path length (estimate)'),
+]
+
+
+def plot_mode(mode: str) -> None:
+    df = pd.read_csv(f'evolution_{mode}.csv')
+
+    fig, axes = plt.subplots(5, 2, figsize=(12, 16))
+    for ax, (column, title) in zip(axes.flat, METRICS):
+        ax.plot(df['year'], df[column], color='skyblue', marker='o', markerfacecolor='black', markersize=3)
+        ax.set_title(title)
+        ax.set_xlabel('Year')
+        ax.grid(axis='y', which='both', linestyle='--', linewidth=0.5)
+    fig.suptitle(f'DBLP network evolution ({mode})')
+    fig.tight_layout()
+    fig.savefig(f'evolution_{mode}.png')
+    plt.close(fig)
+
+
+def main():
+    for mode in ['snapshot', 'cumulative']:
+        plot_mode(mode)
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,233 @@
+use std::fs::write;
+
+use rayon_hash::{HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YearActivity {
+    pub year: u32,
+    pub degree: usize,
+    pub strength: usize,
+    // Coauthors never seen in any earlier year
+    pub new_coauthors: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorProfile {
+    pub author: usize,
+    pub first_year: u32,
+    pub last_year: u32,
+    pub years: Vec<YearActivity>,
+    // Share of yearly coauthor links that repeat a coauthor from an earlier year
+    pub repeat_ratio: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EgoNetwork {
+    pub ego: usize,
+    // None when the snapshot covers every year
+    pub year: Option<u32>,
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize, usize)>,
+}
+
+impl EgoNetwork {
+    pub fn to_json(&self) -> String {
+        let year = match self.year {
+            Some(year) => year.to_string(),
+            None => String::from("null"),
+        };
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        let edges = self
+            .edges
+            .iter()
+            .map(|(a, b, w)| format!("[{},{},{}]", a, b, w))
+            .collect::<Vec<String>>()
+            .join(",");
+        format!(
+            "{{\"ego\":{},\"year\":{},\"nodes\":[{}],\"edges\":[{}]}}",
+            self.ego, year, nodes, edges
+        )
+    }
+}
+
+/// Per-author view of the yearly coauthorship matrices: author -> year -> coauthor -> weight.
+/// Both directions are stored, unlike the `min -> max` layout returned by `parse_author_file`.
+pub struct AuthorIndex {
+    timeline: HashMap<usize, HashMap<u32, HashMap<usize, usize>>>,
+}
+
+impl AuthorIndex {
+    pub fn new(data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>) -> Self {
+        let mut timeline: HashMap<usize, HashMap<u32, HashMap<usize, usize>>> = HashMap::new();
+        for (&year, year_data) in data.iter() {
+            for (&author1, links) in year_data.iter() {
+                for (&author2, &weight) in links.iter() {
+                    if author1 == author2 {
+                        continue;
+                    }
+                    *timeline
+                        .entry(author1)
+                        .or_default()
+                        .entry(year)
+                        .or_default()
+                        .entry(author2)
+                        .or_insert(0) += weight;
+                    *timeline
+                        .entry(author2)
+                        .or_default()
+                        .entry(year)
+                        .or_default()
+                        .entry(author1)
+                        .or_insert(0) += weight;
+                }
+            }
+        }
+        AuthorIndex { timeline }
+    }
+
+    pub fn contains(&self, author: usize) -> bool {
+        self.timeline.contains_key(&author)
+    }
+
+    pub fn active_years(&self, author: usize) -> Vec<u32> {
+        let mut years: Vec<u32> = match self.timeline.get(&author) {
+            Some(years) => years.keys().cloned().collect(),
+            None => return Vec::new(),
+        };
+        years.sort_unstable();
+        years
+    }
+
+    pub fn coauthors(&self, author: usize, year: u32) -> Option<&HashMap<usize, usize>> {
+        self.timeline.get(&author)?.get(&year)
+    }
+
+    pub fn profile(&self, author: usize) -> Option<AuthorProfile> {
+        let years = self.active_years(author);
+        let first_year = *years.first()?;
+        let last_year = *years.last()?;
+
+        let mut seen: HashSet<usize> = HashSet::new();
+        let mut links = 0;
+        let mut repeats = 0;
+        let mut activity = Vec::with_capacity(years.len());
+        for year in years {
+            let coauthors = self.coauthors(author, year).unwrap();
+            let mut new_coauthors = 0;
+            for &coauthor in coauthors.keys() {
+                links += 1;
+                if seen.contains(&coauthor) {
+                    repeats += 1;
+                } else {
+                    new_coauthors += 1;
+                }
+            }
+            seen.extend(coauthors.keys().cloned());
+            activity.push(YearActivity {
+                year,
+                degree: coauthors.len(),
+                strength: coauthors.values().sum(),
+                new_coauthors,
+            });
+        }
+
+        Some(AuthorProfile {
+            author,
+            first_year,
+            last_year,
+            years: activity,
+            repeat_ratio: if links == 0 {
+                0.0
+            } else {
+                repeats as f64 / links as f64
+            },
+        })
+    }
+
+    fn links_in(&self, author: usize, year: Option<u32>) -> HashMap<usize, usize> {
+        let mut result: HashMap<usize, usize> = HashMap::new();
+        if let Some(years) = self.timeline.get(&author) {
+            for (&y, coauthors) in years.iter() {
+                if year.is_some() && year != Some(y) {
+                    continue;
+                }
+                for (&coauthor, &weight) in coauthors.iter() {
+                    *result.entry(coauthor).or_insert(0) += weight;
+                }
+            }
+        }
+        result
+    }
+
+    /// Ego, its coauthors and the links among them, either in one year or over all years.
+    pub fn ego_network(&self, author: usize, year: Option<u32>) -> Option<EgoNetwork> {
+        if !self.contains(author) {
+            return None;
+        }
+        let ego_links = self.links_in(author, year);
+
+        let mut nodes: Vec<usize> = ego_links.keys().cloned().collect();
+        nodes.push(author);
+        nodes.sort_unstable();
+        let members: HashSet<usize> = nodes.iter().cloned().collect();
+
+        let mut edges = Vec::new();
+        for &node in nodes.iter() {
+            let links = if node == author {
+                ego_links.clone()
+            } else {
+                self.links_in(node, year)
+            };
+            for (&other, &weight) in links.iter() {
+                if node < other && members.contains(&other) {
+                    edges.push((node, other, weight));
+                }
+            }
+        }
+        edges.sort_unstable();
+
+        Some(EgoNetwork {
+            ego: author,
+            year,
+            nodes,
+            edges,
+        })
+    }
+
+    /// Writes one ego network per active year as a JSON array.
+    pub fn write_ego_networks_json(&self, path: &str, author: usize) {
+        let snapshots = self
+            .active_years(author)
+            .into_iter()
+            .filter_map(|year| self.ego_network(author, Some(year)))
+            .map(|ego| ego.to_json())
+            .collect::<Vec<String>>();
+        write(path, format!("[{}]", snapshots.join(",\n")).as_bytes()).unwrap();
+    }
+}
+
+pub fn author_report(index: &AuthorIndex, author: usize) {
+    let profile = match index.profile(author) {
+        Some(profile) => profile,
+        None => {
+            println!("Author {} not found", author);
+            return;
+        }
+    };
+    println!(
+        "Author {}: active {}-{}, repeat collaboration ratio: {}",
+        profile.author, profile.first_year, profile.last_year, profile.repeat_ratio
+    );
+    for activity in profile.years.iter() {
+        println!(
+            "{}: degree {}, strength {}, new coauthors {}",
+            activity.year, activity.degree, activity.strength, activity.new_coauthors
+        );
+    }
+    index.write_ego_networks_json(&format!("ego_{}.json", author), author);
+}
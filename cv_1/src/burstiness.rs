@@ -0,0 +1,184 @@
+use std::fs::write;
+use std::hash::Hash;
+
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterEventStats {
+    pub events: usize,
+    pub mean_gap: f64,
+    pub std_gap: f64,
+    // (std - mean) / (std + mean), None when every gap is zero
+    pub burstiness: Option<f64>,
+    // Correlation of consecutive gaps, None with fewer than three gaps or constant gaps
+    pub memory: Option<f64>,
+}
+
+/// Gaps between consecutive events. Several papers in the same year produce zero gaps.
+pub fn inter_event_times(times: &[u32]) -> Vec<u32> {
+    let mut sorted = times.to_vec();
+    sorted.sort_unstable();
+    sorted.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+/// Mean and population standard deviation.
+pub(crate) fn mean_std<T: Copy + Into<f64>>(values: &[T]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = values.iter().map(|&v| v.into()).sum::<f64>() / values.len() as f64;
+    let variance = values
+        .iter()
+        .map(|&v| (v.into() - mean).powi(2))
+        .sum::<f64>()
+        / values.len() as f64;
+    (mean, variance.sqrt())
+}
+
+pub fn burstiness(gaps: &[u32]) -> Option<f64> {
+    let (mean, std) = mean_std(gaps);
+    if gaps.is_empty() || mean + std == 0.0 {
+        return None;
+    }
+    Some((std - mean) / (std + mean))
+}
+
+/// Memory coefficient of Goh and Barabási: Pearson correlation of (gap_i, gap_i+1).
+pub fn memory_coefficient(gaps: &[u32]) -> Option<f64> {
+    if gaps.len() < 3 {
+        return None;
+    }
+    let (mean1, std1) = mean_std(&gaps[..gaps.len() - 1]);
+    let (mean2, std2) = mean_std(&gaps[1..]);
+    if std1 == 0.0 || std2 == 0.0 {
+        return None;
+    }
+    let sum: f64 = gaps
+        .windows(2)
+        .map(|w| (w[0] as f64 - mean1) * (w[1] as f64 - mean2))
+        .sum();
+    Some(sum / ((gaps.len() - 1) as f64 * std1 * std2))
+}
+
+pub fn inter_event_stats(times: &[u32]) -> InterEventStats {
+    let gaps = inter_event_times(times);
+    let (mean_gap, std_gap) = mean_std(&gaps);
+    InterEventStats {
+        events: times.len(),
+        mean_gap,
+        std_gap,
+        burstiness: burstiness(&gaps),
+        memory: memory_coefficient(&gaps),
+    }
+}
+
+/// Publication years of every author, one entry per paper.
+pub fn author_event_times(papers: &[(u32, Vec<usize>)]) -> HashMap<usize, Vec<u32>> {
+    let mut result: HashMap<usize, Vec<u32>> = HashMap::new();
+    for (time, authors) in papers.iter() {
+        let mut authors = authors.clone();
+        authors.sort_unstable();
+        authors.dedup();
+        for author in authors {
+            result.entry(author).or_default().push(*time);
+        }
+    }
+    result
+}
+
+/// Years of the joint papers of every author pair, keyed by `(min, max)`.
+pub fn pair_event_times(papers: &[(u32, Vec<usize>)]) -> HashMap<(usize, usize), Vec<u32>> {
+    let mut result: HashMap<(usize, usize), Vec<u32>> = HashMap::new();
+    for (time, authors) in papers.iter() {
+        let mut authors = authors.clone();
+        authors.sort_unstable();
+        authors.dedup();
+        for i in 0..authors.len() {
+            for j in i + 1..authors.len() {
+                result
+                    .entry((authors[i], authors[j]))
+                    .or_default()
+                    .push(*time);
+            }
+        }
+    }
+    result
+}
+
+/// Statistics for every key with at least `min_events` events.
+pub fn inter_event_stats_par<K>(
+    events: &HashMap<K, Vec<u32>>,
+    min_events: usize,
+) -> HashMap<K, InterEventStats>
+where
+    K: Eq + Hash + Copy + Send + Sync,
+{
+    events
+        .into_par_iter()
+        .filter(|(_, times)| times.len() >= min_events)
+        .map(|(&key, times)| (key, inter_event_stats(times)))
+        .collect()
+}
+
+/// Pooled histogram of gaps over all keys, sorted by gap.
+pub fn gap_distribution<K>(events: &HashMap<K, Vec<u32>>) -> Vec<(u32, usize)>
+where
+    K: Eq + Hash,
+{
+    let mut distribution: HashMap<u32, usize> = HashMap::new();
+    for times in events.values() {
+        for gap in inter_event_times(times) {
+            *distribution.entry(gap).or_insert(0) += 1;
+        }
+    }
+    let mut distribution_vec: Vec<(u32, usize)> = distribution.into_iter().collect();
+    distribution_vec.sort_by_key(|a| a.0);
+    distribution_vec
+}
+
+fn summarize<K>(name: &str, events: &HashMap<K, Vec<u32>>, min_events: usize)
+where
+    K: Eq + Hash + Copy + Send + Sync,
+{
+    let start = std::time::Instant::now();
+    let stats = inter_event_stats_par(events, min_events);
+
+    let burstiness: Vec<f64> = stats.values().filter_map(|s| s.burstiness).collect();
+    let memory: Vec<f64> = stats.values().filter_map(|s| s.memory).collect();
+    let avg = |values: &[f64]| {
+        if values.is_empty() {
+            0.0
+        } else {
+            values.iter().sum::<f64>() / values.len() as f64
+        }
+    };
+
+    let distribution = gap_distribution(events);
+    write(
+        format!("{}_gaps.txt", name),
+        distribution
+            .iter()
+            .map(|(k, v)| format!("{} {}", k, v))
+            .collect::<Vec<String>>()
+            .join("\n")
+            .as_bytes(),
+    )
+    .unwrap();
+
+    let end = std::time::Instant::now();
+    println!(
+        "{}: {} with >= {} events, average burstiness: {}, average memory: {} in {}",
+        name,
+        stats.len(),
+        min_events,
+        avg(&burstiness),
+        avg(&memory),
+        (end - start).as_millis()
+    );
+}
+
+pub fn burstiness_report(papers: &[(u32, Vec<usize>)], min_events: usize) {
+    summarize("author", &author_event_times(papers), min_events);
+    summarize("pair", &pair_event_times(papers), min_events);
+}
@@ -0,0 +1,265 @@
+use std::collections::VecDeque;
+use std::fs::write;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+use crate::graph::IndexedGraph;
+
+/// `degree / (n - 1)`.
+pub fn degree_centrality(graph: &IndexedGraph) -> Vec<f64> {
+    let scale = 1.0 / (graph.len().max(2) - 1) as f64;
+    (0..graph.len())
+        .map(|node| graph.degree(node) as f64 * scale)
+        .collect()
+}
+
+/// Hop distances from `source`, `usize::MAX` for unreachable nodes.
+fn bfs(graph: &IndexedGraph, source: usize) -> Vec<usize> {
+    let mut distances = vec![usize::MAX; graph.len()];
+    let mut queue = VecDeque::new();
+    distances[source] = 0;
+    queue.push_back(source);
+    while let Some(node) = queue.pop_front() {
+        for &(neighbor, _) in graph.neighbors[node].iter() {
+            if distances[neighbor] == usize::MAX {
+                distances[neighbor] = distances[node] + 1;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    distances
+}
+
+/// Closeness with the Wasserman-Faust correction for disconnected graphs: `(r - 1) / sum d`
+/// scaled by `(r - 1) / (n - 1)`, where `r` counts the nodes reachable from the node (itself
+/// included). Both closeness and harmonic centrality come from the same BFS per node.
+pub fn closeness_centrality(graph: &IndexedGraph) -> Vec<f64> {
+    distance_centralities(graph).0
+}
+
+/// `sum 1 / d(v, u)` over the reachable nodes `u != v`, not normalized.
+pub fn harmonic_centrality(graph: &IndexedGraph) -> Vec<f64> {
+    distance_centralities(graph).1
+}
+
+/// `(closeness, harmonic)` of every node, one BFS per node in parallel.
+pub fn distance_centralities(graph: &IndexedGraph) -> (Vec<f64>, Vec<f64>) {
+    let n = graph.len();
+    (0..n)
+        .into_par_iter()
+        .map(|source| {
+            let mut reachable = 0;
+            let mut total = 0;
+            let mut harmonic = 0.0;
+            for d in bfs(graph, source) {
+                if d == usize::MAX || d == 0 {
+                    continue;
+                }
+                reachable += 1;
+                total += d;
+                harmonic += 1.0 / d as f64;
+            }
+            let closeness = if total == 0 {
+                0.0
+            } else {
+                reachable as f64 / total as f64 * reachable as f64 / (n - 1) as f64
+            };
+            (closeness, harmonic)
+        })
+        .unzip()
+}
+
+/// Dependencies of all nodes on the shortest paths from `source` (Brandes), accumulated into
+/// `result`.
+fn accumulate_dependencies(graph: &IndexedGraph, source: usize, result: &mut [f64]) {
+    let n = graph.len();
+    let mut stack = Vec::with_capacity(n);
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut sigma = vec![0.0; n];
+    let mut distances = vec![usize::MAX; n];
+    sigma[source] = 1.0;
+    distances[source] = 0;
+    let mut queue = VecDeque::new();
+    queue.push_back(source);
+    while let Some(v) = queue.pop_front() {
+        stack.push(v);
+        for &(w, _) in graph.neighbors[v].iter() {
+            if distances[w] == usize::MAX {
+                distances[w] = distances[v] + 1;
+                queue.push_back(w);
+            }
+            if distances[w] == distances[v] + 1 {
+                sigma[w] += sigma[v];
+                predecessors[w].push(v);
+            }
+        }
+    }
+
+    let mut delta = vec![0.0; n];
+    while let Some(w) = stack.pop() {
+        for &v in predecessors[w].iter() {
+            delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+        }
+        if w != source {
+            result[w] += delta[w];
+        }
+    }
+}
+
+fn betweenness_from(graph: &IndexedGraph, sources: &[usize], normalized: bool) -> Vec<f64> {
+    let n = graph.len();
+    let mut result = sources
+        .par_iter()
+        .fold(
+            || vec![0.0; n],
+            |mut partial, &source| {
+                accumulate_dependencies(graph, source, &mut partial);
+                partial
+            },
+        )
+        .reduce(
+            || vec![0.0; n],
+            |mut a, b| {
+                for (x, y) in a.iter_mut().zip(b) {
+                    *x += y;
+                }
+                a
+            },
+        );
+    // every pair is counted from both ends, sampled sources are extrapolated to all n
+    let mut scale = 0.5 * n as f64 / sources.len().max(1) as f64;
+    if normalized && n > 2 {
+        scale *= 2.0 / ((n - 1) * (n - 2)) as f64;
+    }
+    for value in result.iter_mut() {
+        *value *= scale;
+    }
+    result
+}
+
+/// Exact betweenness by Brandes' algorithm, parallel over the sources. `normalized` divides by
+/// the `(n - 1)(n - 2) / 2` pairs not containing the node.
+pub fn betweenness_centrality(graph: &IndexedGraph, normalized: bool) -> Vec<f64> {
+    let sources: Vec<usize> = (0..graph.len()).collect();
+    betweenness_from(graph, &sources, normalized)
+}
+
+/// Brandes from `samples` sources drawn without replacement, scaled by `n / samples`
+/// (Brandes and Pich).
+pub fn approximate_betweenness(
+    graph: &IndexedGraph,
+    samples: usize,
+    seed: u64,
+    normalized: bool,
+) -> Vec<f64> {
+    let mut sources: Vec<usize> = (0..graph.len()).collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    sources.shuffle(&mut rng);
+    sources.truncate(samples.max(1));
+    betweenness_from(graph, &sources, normalized)
+}
+
+/// `k` largest values as `(node index, value)`, ties by the smaller index.
+pub fn top_k(values: &[f64], k: usize) -> Vec<(usize, f64)> {
+    let mut order: Vec<(usize, f64)> = values.iter().cloned().enumerate().collect();
+    order.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+    order.truncate(k);
+    order
+}
+
+/// Prints the top `k` node ids with their values rounded to `precision` digits.
+pub(crate) fn print_top(
+    name: &str,
+    label: &str,
+    graph: &IndexedGraph,
+    values: &[f64],
+    k: usize,
+    precision: usize,
+) {
+    let top: Vec<String> = top_k(values, k)
+        .iter()
+        .map(|&(node, value)| format!("{} ({:.*})", graph.ids[node], precision, value))
+        .collect();
+    println!("{} top {} {}: {}", name, k, label, top.join(", "));
+}
+
+/// All centralities with the top `k` nodes of each. Exact betweenness is compared with the
+/// approximation from `samples` sources, the table goes to `{name}_node_centrality.csv`.
+pub fn centrality_report(
+    name: &str,
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    k: usize,
+    samples: usize,
+) {
+    let graph = IndexedGraph::new(sparse_matrix);
+
+    let start = std::time::Instant::now();
+    let degree = degree_centrality(&graph);
+    let end = std::time::Instant::now();
+    println!(
+        "{} degree centrality in {}",
+        name,
+        (end - start).as_millis()
+    );
+
+    let start = std::time::Instant::now();
+    let (closeness, harmonic) = distance_centralities(&graph);
+    let end = std::time::Instant::now();
+    println!(
+        "{} closeness and harmonic centrality in {}",
+        name,
+        (end - start).as_millis()
+    );
+
+    let start = std::time::Instant::now();
+    let betweenness = betweenness_centrality(&graph, true);
+    let end = std::time::Instant::now();
+    println!("{} betweenness in {}", name, (end - start).as_millis());
+
+    let start = std::time::Instant::now();
+    let approximate = approximate_betweenness(&graph, samples, 420, true);
+    let end = std::time::Instant::now();
+    let exact_top: Vec<usize> = top_k(&betweenness, k).iter().map(|t| t.0).collect();
+    let shared = top_k(&approximate, k)
+        .iter()
+        .filter(|t| exact_top.contains(&t.0))
+        .count();
+    println!(
+        "{} betweenness from {} sources: {} of the exact top {} in {}",
+        name,
+        samples,
+        shared,
+        k,
+        (end - start).as_millis()
+    );
+
+    print_top(name, "degree", &graph, &degree, k, 4);
+    print_top(name, "closeness", &graph, &closeness, k, 4);
+    print_top(name, "harmonic", &graph, &harmonic, k, 4);
+    print_top(name, "betweenness", &graph, &betweenness, k, 4);
+    print_top(name, "approximate betweenness", &graph, &approximate, k, 4);
+
+    let mut lines = vec![String::from(
+        "node,degree,closeness,harmonic,betweenness,approximate_betweenness",
+    )];
+    for node in 0..graph.len() {
+        lines.push(format!(
+            "{},{},{},{},{},{}",
+            graph.ids[node],
+            degree[node],
+            closeness[node],
+            harmonic[node],
+            betweenness[node],
+            approximate[node]
+        ));
+    }
+    write(
+        format!("{}_node_centrality.csv", name),
+        lines.join("\n").as_bytes(),
+    )
+    .unwrap();
+}
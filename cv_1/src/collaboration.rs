@@ -0,0 +1,153 @@
+use std::cmp::Ordering;
+
+use rayon_hash::HashMap;
+
+use crate::cv_2::aggregate_weights;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollaborationRanking {
+    TotalWeight,
+    AveragePerActiveYear,
+    Span,
+    // Share of each author's strength taken by the pair, ranked by the smaller of the two shares
+    NormalizedWeight,
+}
+
+impl CollaborationRanking {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollaborationRanking::TotalWeight => "total weight",
+            CollaborationRanking::AveragePerActiveYear => "average weight per active year",
+            CollaborationRanking::Span => "collaboration span",
+            CollaborationRanking::NormalizedWeight => "normalized weight",
+        }
+    }
+}
+
+/// Aggregated history of one author pair, `author1 < author2`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collaboration {
+    pub author1: usize,
+    pub author2: usize,
+    pub total_weight: usize,
+    pub active_years: usize,
+    pub first_year: u32,
+    pub last_year: u32,
+    // total_weight divided by the strength of author1 / author2 over all years
+    pub share1: f64,
+    pub share2: f64,
+}
+
+impl Collaboration {
+    pub fn average_weight(&self) -> f64 {
+        self.total_weight as f64 / self.active_years as f64
+    }
+
+    pub fn span(&self) -> u32 {
+        self.last_year - self.first_year + 1
+    }
+
+    pub fn normalized_weight(&self) -> f64 {
+        self.share1.min(self.share2)
+    }
+
+    pub fn score(&self, ranking: CollaborationRanking) -> f64 {
+        match ranking {
+            CollaborationRanking::TotalWeight => self.total_weight as f64,
+            CollaborationRanking::AveragePerActiveYear => self.average_weight(),
+            CollaborationRanking::Span => self.span() as f64,
+            CollaborationRanking::NormalizedWeight => self.normalized_weight(),
+        }
+    }
+}
+
+pub fn collect_collaborations(
+    data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>,
+) -> Vec<Collaboration> {
+    let aggregate = aggregate_weights(data);
+
+    let mut strengths: HashMap<usize, usize> = HashMap::new();
+    for (&author1, links) in aggregate.iter() {
+        for (&author2, &weight) in links.iter() {
+            *strengths.entry(author1).or_insert(0) += weight;
+            *strengths.entry(author2).or_insert(0) += weight;
+        }
+    }
+
+    // (active years, first year, last year) per pair
+    let mut activity: HashMap<(usize, usize), (usize, u32, u32)> = HashMap::new();
+    for (&year, year_data) in data.iter() {
+        for (&author1, links) in year_data.iter() {
+            for &author2 in links.keys() {
+                activity
+                    .entry((author1, author2))
+                    .and_modify(|e| {
+                        e.0 += 1;
+                        e.1 = e.1.min(year);
+                        e.2 = e.2.max(year);
+                    })
+                    .or_insert((1, year, year));
+            }
+        }
+    }
+
+    let mut result = Vec::with_capacity(activity.len());
+    for (&author1, links) in aggregate.iter() {
+        for (&author2, &weight) in links.iter() {
+            let (active_years, first_year, last_year) = activity[&(author1, author2)];
+            result.push(Collaboration {
+                author1,
+                author2,
+                total_weight: weight,
+                active_years,
+                first_year,
+                last_year,
+                share1: weight as f64 / strengths[&author1] as f64,
+                share2: weight as f64 / strengths[&author2] as f64,
+            });
+        }
+    }
+
+    result
+}
+
+/// The `k` best pairs for the given ranking. Equal scores are ordered by total weight and then by
+/// the author ids, so the result does not depend on hash map iteration order.
+pub fn top_collaborations(
+    collaborations: &[Collaboration],
+    ranking: CollaborationRanking,
+    k: usize,
+) -> Vec<Collaboration> {
+    let mut sorted: Vec<&Collaboration> = collaborations.iter().collect();
+    sorted.sort_by(|a, b| {
+        b.score(ranking)
+            .partial_cmp(&a.score(ranking))
+            .unwrap_or(Ordering::Equal)
+            .then(b.total_weight.cmp(&a.total_weight))
+            .then(a.author1.cmp(&b.author1))
+            .then(a.author2.cmp(&b.author2))
+    });
+    sorted.into_iter().take(k).cloned().collect()
+}
+
+pub fn collaboration_report(collaborations: &[Collaboration], k: usize) {
+    for ranking in [
+        CollaborationRanking::TotalWeight,
+        CollaborationRanking::AveragePerActiveYear,
+        CollaborationRanking::Span,
+        CollaborationRanking::NormalizedWeight,
+    ] {
+        println!("Top {} collaborations by {}:", k, ranking.name());
+        for c in top_collaborations(collaborations, ranking, k) {
+            println!(
+                "Author1: {}, Author2: {}, Score: {}, Weight: {}, Years: {}-{}",
+                c.author1,
+                c.author2,
+                c.score(ranking),
+                c.total_weight,
+                c.first_year,
+                c.last_year
+            );
+        }
+    }
+}
@@ -1,37 +1,18 @@
-use rayon_hash::HashMap;
-use std::fs::read_to_string;
-
 use crate::functions::{
-    get_avg_cm_nb, get_avg_dg, get_cl_ds, get_cl_ef, get_cl_ef_dis, get_dg_dis, get_max_cm_ng,
-    get_max_dg,
+    get_avg_cm_nb, get_avg_dg, get_cl_ds, get_cl_ef_dis, get_dg_dis, get_max_cm_ng, get_max_dg,
 };
 use crate::functions_par::{
-    get_avg_cm_nb_par, get_avg_dg_par, get_cl_ds_par, get_cl_ef_dis_par, get_cl_ef_par,
-    get_dg_dis_par, get_max_cm_ng_par, get_max_dg_par,
+    get_avg_cm_nb_par, get_avg_dg_par, get_cl_ds_par, get_cl_ef_dis_par, get_dg_dis_par,
+    get_max_cm_ng_par, get_max_dg_par,
 };
-
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
-}
+use crate::graph::read_edge_list;
+use crate::ground_truth::{ground_truth_report, Cover};
+use crate::louvain::{louvain, LouvainConfig};
+use crate::path_prediction::path_prediction_report;
+use crate::spectral_centrality::spectral_centrality_report;
 
 pub fn cv_1() {
-    let mut sparse_matrix: HashMap<usize, HashMap<usize, usize>> = HashMap::new();
-    let lines = read_lines("com-youtube.ungraph.txt");
-
-    for line in lines {
-        let mut iter = line.split_whitespace();
-        let from = iter.next().unwrap().parse::<usize>().unwrap();
-        let to = iter.next().unwrap().parse::<usize>().unwrap();
-        sparse_matrix
-            .entry(from)
-            .or_insert(HashMap::new())
-            .entry(to)
-            .or_insert(1);
-    }
+    let sparse_matrix = read_edge_list("com-youtube.ungraph.txt");
 
     get_avg_dg(&sparse_matrix);
     get_avg_dg_par(&sparse_matrix);
@@ -43,8 +24,8 @@ pub fn cv_1() {
     get_dg_dis_par(&sparse_matrix);
 
     /*
-    get_cl_ef(&sparse_matrix);
-    get_cl_ef_par(&sparse_matrix);
+    crate::functions::get_cl_ef(&sparse_matrix);
+    crate::functions_par::get_cl_ef_par(&sparse_matrix);
     */
 
     get_cl_ef_dis(&sparse_matrix);
@@ -58,4 +39,19 @@ pub fn cv_1() {
 
     get_max_cm_ng(&sparse_matrix);
     get_max_cm_ng_par(&sparse_matrix);
+
+    path_prediction_report(&sparse_matrix, 20);
+    spectral_centrality_report("youtube", &sparse_matrix, 10);
+
+    let truth = Cover::read_snap("com-youtube.top5000.cmty.txt");
+    let start = std::time::Instant::now();
+    let result = louvain(&sparse_matrix, &LouvainConfig::default());
+    let end = std::time::Instant::now();
+    println!(
+        "Louvain: {} communities, modularity {} in {}",
+        result.partition.community_count(),
+        result.modularity,
+        (end - start).as_millis()
+    );
+    ground_truth_report("Louvain", &Cover::from_partition(&result.partition), &truth);
 }
@@ -5,41 +5,61 @@ use std::{
 
 use rayon_hash::HashMap;
 
-fn parse_author_file(
+use crate::author_profile::{author_report, AuthorIndex};
+use crate::burstiness::burstiness_report;
+use crate::collaboration::{
+    collaboration_report, collect_collaborations, top_collaborations, CollaborationRanking,
+};
+use crate::evolution::evolution_report;
+use crate::temporal::temporal_report;
+
+/// Papers as (year, authors) in file order, read one at a time from the nverts, simplices and
+/// times files.
+fn read_simplices(
     nverts_path: &str,
     simplices_path: &str,
     times_path: &str,
-) -> HashMap<u32, HashMap<usize, HashMap<usize, usize>>> {
+) -> impl Iterator<Item = (u32, Vec<usize>)> {
     let nverts_file = BufReader::new(File::open(nverts_path).unwrap());
     let mut simplices_file = BufReader::new(File::open(simplices_path).unwrap()).lines();
     let times_file = BufReader::new(File::open(times_path).unwrap());
 
-    let mut result: HashMap<u32, HashMap<usize, HashMap<usize, usize>>> = HashMap::new();
+    nverts_file
+        .lines()
+        .zip(times_file.lines())
+        .map(move |(num, time)| {
+            let num: usize = num.unwrap().parse().unwrap();
+            let time: u32 = time.unwrap().parse().unwrap();
+
+            let mut simplex = Vec::with_capacity(num);
+            for _ in 0..num {
+                simplex.push(
+                    simplices_file
+                        .next()
+                        .unwrap()
+                        .unwrap()
+                        .parse::<usize>()
+                        .unwrap(),
+                );
+            }
+            (time, simplex)
+        })
+}
 
-    for (num, time) in nverts_file.lines().zip(times_file.lines()) {
-        let num: usize = num.unwrap().parse().unwrap();
-        let time: u32 = time.unwrap().parse().unwrap();
-
-        let mut simplex = Vec::with_capacity(num);
-        for _ in 0..num {
-            simplex.push(
-                simplices_file
-                    .next()
-                    .unwrap()
-                    .unwrap()
-                    .parse::<usize>()
-                    .unwrap(),
-            );
-        }
+pub fn parse_author_file(
+    nverts_path: &str,
+    simplices_path: &str,
+    times_path: &str,
+) -> HashMap<u32, HashMap<usize, HashMap<usize, usize>>> {
+    let mut result: HashMap<u32, HashMap<usize, HashMap<usize, usize>>> = HashMap::new();
 
+    for (time, simplex) in read_simplices(nverts_path, simplices_path, times_path) {
         for i in 0..simplex.len() {
             for j in i + 1..simplex.len() {
                 let author1 = simplex[i];
                 let author2 = simplex[j];
-                let year_entry = result.entry(time).or_insert_with(HashMap::new);
-                let author1_entry = year_entry
-                    .entry(author1.min(author2))
-                    .or_insert_with(HashMap::new);
+                let year_entry = result.entry(time).or_default();
+                let author1_entry = year_entry.entry(author1.min(author2)).or_default();
                 *author1_entry.entry(author1.max(author2)).or_insert(0) += 1;
             }
         }
@@ -48,6 +68,15 @@ fn parse_author_file(
     result
 }
 
+/// Every paper as (year, authors), in file order.
+pub fn parse_papers(
+    nverts_path: &str,
+    simplices_path: &str,
+    times_path: &str,
+) -> Vec<(u32, Vec<usize>)> {
+    read_simplices(nverts_path, simplices_path, times_path).collect()
+}
+
 fn compute_degrees_and_weighted_clustering(
     data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>,
 ) -> HashMap<u32, (f64, f64, f64)> {
@@ -87,7 +116,7 @@ fn compute_weighted_clustering_coefficient(matrix: &HashMap<usize, HashMap<usize
         .max()
         .unwrap_or(1) as f64;
 
-    for (&node, neighbors) in matrix {
+    for neighbors in matrix.values() {
         let k = neighbors.len() as f64;
 
         if k <= 1.0 {
@@ -104,7 +133,7 @@ fn compute_weighted_clustering_coefficient(matrix: &HashMap<usize, HashMap<usize
                 if neighbor1 != neighbor2
                     && matrix.get(&neighbor1).unwrap().contains_key(&neighbor2)
                 {
-                    sum_weights += (weight1 as f64 + weight2 as f64);
+                    sum_weights += weight1 as f64 + weight2 as f64;
                 }
             }
         }
@@ -116,14 +145,14 @@ fn compute_weighted_clustering_coefficient(matrix: &HashMap<usize, HashMap<usize
     total_coefficient / matrix.len() as f64
 }
 
-fn aggregate_weights(
+pub fn aggregate_weights(
     data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>,
 ) -> HashMap<usize, HashMap<usize, usize>> {
     let mut aggregate: HashMap<usize, HashMap<usize, usize>> = HashMap::new();
 
     for year_data in data.values() {
         for (&author1, author_data) in year_data.iter() {
-            let author1_entry = aggregate.entry(author1).or_insert_with(HashMap::new);
+            let author1_entry = aggregate.entry(author1).or_default();
             for (&author2, &weight) in author_data.iter() {
                 *author1_entry.entry(author2).or_insert(0) += weight;
             }
@@ -133,40 +162,33 @@ fn aggregate_weights(
     aggregate
 }
 
-fn find_max_average_weight_authors(
-    aggregate: &HashMap<usize, HashMap<usize, usize>>,
-) -> (usize, usize, f64) {
-    let mut max_average = 0.0;
-    let mut max_author1 = 0;
-    let mut max_author2 = 0;
-
-    for (&author1, author_data) in aggregate.iter() {
-        for (&author2, &weight) in author_data.iter() {
-            let average = weight as f64 / aggregate.len() as f64;
-
-            if average > max_average {
-                max_average = average;
-                max_author1 = author1;
-                max_author2 = author2;
-            }
-        }
-    }
-
-    (max_author1, max_author2, max_average)
-}
-
 pub fn cv_2() {
-    let mut sparse_matrices: HashMap<u32, HashMap<usize, HashMap<usize, usize>>> =
-        parse_author_file(
-            "coauth-DBLP-nverts.txt",
-            "coauth-DBLP-simplices.txt",
-            "coauth-DBLP-times.txt",
-        );
+    let sparse_matrices: HashMap<u32, HashMap<usize, HashMap<usize, usize>>> = parse_author_file(
+        "coauth-DBLP-nverts.txt",
+        "coauth-DBLP-simplices.txt",
+        "coauth-DBLP-times.txt",
+    );
 
     let mut degrees_over_time = compute_degrees_and_weighted_clustering(&sparse_matrices);
     println!("{:?}", degrees_over_time.remove(&2010));
 
-    let aggregate = aggregate_weights(&sparse_matrices);
-    let (author1, author2, average) = find_max_average_weight_authors(&aggregate);
-    println!("Author1: {}, Author2: {}", author1, author2);
+    evolution_report(&sparse_matrices);
+
+    let collaborations = collect_collaborations(&sparse_matrices);
+    collaboration_report(&collaborations, 10);
+
+    let index = AuthorIndex::new(&sparse_matrices);
+    if let Some(top) =
+        top_collaborations(&collaborations, CollaborationRanking::TotalWeight, 1).first()
+    {
+        author_report(&index, top.author1);
+        temporal_report(&sparse_matrices, top.author1);
+    }
+
+    let papers = parse_papers(
+        "coauth-DBLP-nverts.txt",
+        "coauth-DBLP-simplices.txt",
+        "coauth-DBLP-times.txt",
+    );
+    burstiness_report(&papers, 3);
 }
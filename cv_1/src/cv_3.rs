@@ -0,0 +1,25 @@
+use crate::cv_2::{aggregate_weights, parse_author_file};
+use crate::link_evaluation::{cross_validation_report, temporal_holdout_report};
+use crate::link_prediction::link_prediction_report;
+use crate::path_prediction::path_cross_validation_report;
+use crate::spectral_centrality::spectral_centrality_report;
+use crate::temporal_prediction::temporal_prediction_report;
+
+pub fn cv_3() {
+    let sparse_matrices = parse_author_file(
+        "coauth-DBLP-nverts.txt",
+        "coauth-DBLP-simplices.txt",
+        "coauth-DBLP-times.txt",
+    );
+    let aggregate = aggregate_weights(&sparse_matrices);
+
+    link_prediction_report(&aggregate, 10);
+    spectral_centrality_report("dblp", &aggregate, 10);
+
+    cross_validation_report(&aggregate, 10, 420);
+    path_cross_validation_report(&aggregate, 10, 420);
+
+    let last_year = sparse_matrices.keys().cloned().max().unwrap();
+    temporal_holdout_report(&sparse_matrices, last_year - 1);
+    temporal_prediction_report(&sparse_matrices, last_year - 3, 3, 100);
+}
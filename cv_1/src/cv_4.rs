@@ -0,0 +1,85 @@
+use crate::centrality::centrality_report;
+use crate::flattening::{flatten_all, FlatteningWeight};
+use crate::functions::{get_avg_dg, get_max_dg};
+use crate::layer_similarity::layer_similarity_report;
+use crate::multilayer::MultilayerGraph;
+use crate::multilayer_centrality::{centrality_table, write_centrality_table};
+use crate::random_walk::{occupation_centrality, RandomWalkConfig};
+
+fn print_summary(name: &str, graph: &MultilayerGraph) {
+    println!(
+        "{}: {} nodes, {} layers",
+        name,
+        graph.node_count(),
+        graph.layer_count()
+    );
+    for layer in graph.all_layers() {
+        println!(
+            "Layer {}: {} nodes, {} edges",
+            graph.layer_name(layer),
+            graph.layer(layer).len(),
+            graph.edge_count(layer)
+        );
+    }
+}
+
+pub fn cv_4() {
+    let start = std::time::Instant::now();
+    let lazega = MultilayerGraph::load_lazega("lazega.edges");
+    let aucs = MultilayerGraph::load_aucs("aucs.edges");
+    let end = std::time::Instant::now();
+    println!(
+        "Loaded multilayer networks in {}",
+        (end - start).as_millis()
+    );
+
+    print_summary("lazega", &lazega);
+    print_summary("aucs", &aucs);
+
+    let layers: Vec<usize> = ["1", "2"]
+        .iter()
+        .filter_map(|name| lazega.layer_id(name))
+        .collect();
+    let start = std::time::Instant::now();
+    let table = centrality_table(&lazega, &layers);
+    let end = std::time::Instant::now();
+    println!("Multilayer centralities in {}", (end - start).as_millis());
+    if let Some(row) = lazega.node_id("1").map(|node| &table[node]) {
+        println!("Degree Centrality: {}", row.degree);
+        println!("Degree Deviation: {}", row.degree_deviation);
+        println!("Neighborhood Centrality: {}", row.neighborhood);
+        println!("Connective Redundancy: {}", row.connective_redundancy);
+        println!("Exclusive Neighborhood: {}", row.exclusive_neighborhood);
+    }
+    write_centrality_table("lazega_centrality.csv", &lazega, &table);
+    write_centrality_table(
+        "aucs_centrality.csv",
+        &aucs,
+        &centrality_table(&aucs, &aucs.all_layers()),
+    );
+
+    let start = std::time::Instant::now();
+    let occupation = occupation_centrality(&lazega, &RandomWalkConfig::default());
+    let end = std::time::Instant::now();
+    if let Some(node) = lazega.node_id("1") {
+        println!(
+            "Occupation Centrality: {} in {}",
+            occupation[node],
+            (end - start).as_millis()
+        );
+    }
+
+    let flattened = flatten_all(&aucs, FlatteningWeight::LayerCount);
+    get_avg_dg(&flattened);
+    get_max_dg(&flattened);
+    centrality_report("aucs_flattened", &flattened, 10, 20);
+    centrality_report(
+        "lazega_flattened",
+        &flatten_all(&lazega, FlatteningWeight::LayerCount),
+        10,
+        20,
+    );
+
+    layer_similarity_report("lazega", &lazega);
+    layer_similarity_report("aucs", &aucs);
+}
@@ -0,0 +1,122 @@
+use crate::flattening::{flatten_all, FlatteningWeight};
+use crate::infomap::infomap;
+use crate::label_propagation::{label_propagation_async, label_propagation_semi_sync};
+use crate::louvain::{leiden, louvain, LouvainConfig};
+use crate::multilayer::MultilayerGraph;
+use crate::multislice::{multislice_louvain, MultisliceConfig};
+use crate::partition_quality::{
+    adjusted_rand_index, normalized_mutual_information, partition_report,
+};
+
+pub fn cv_6() {
+    let aucs = MultilayerGraph::load_aucs("aucs.edges");
+    let flattened = flatten_all(&aucs, FlatteningWeight::Unweighted);
+    let config = LouvainConfig::default();
+
+    let start = std::time::Instant::now();
+    let result = louvain(&flattened, &config);
+    let end = std::time::Instant::now();
+    println!(
+        "Louvain: {} communities, modularity {} after {} levels in {}",
+        result.partition.community_count(),
+        result.modularity,
+        result.levels,
+        (end - start).as_millis()
+    );
+    for (i, community) in result.partition.communities().iter().enumerate() {
+        let labels: Vec<&str> = community.iter().map(|&n| aucs.node_label(n)).collect();
+        println!("Community {}: {}", i, labels.join(" "));
+    }
+
+    partition_report("Louvain", &flattened, &result.partition);
+
+    let start = std::time::Instant::now();
+    let leiden_result = leiden(&flattened, &config);
+    let end = std::time::Instant::now();
+    println!(
+        "Leiden: {} communities, modularity {} after {} levels in {}",
+        leiden_result.partition.community_count(),
+        leiden_result.modularity,
+        leiden_result.levels,
+        (end - start).as_millis()
+    );
+    partition_report("Leiden", &flattened, &leiden_result.partition);
+    println!(
+        "Louvain vs Leiden: NMI {}, ARI {}",
+        normalized_mutual_information(&result.partition, &leiden_result.partition),
+        adjusted_rand_index(&result.partition, &leiden_result.partition)
+    );
+
+    let start = std::time::Instant::now();
+    let lpa = label_propagation_async(&flattened, config.seed, 100);
+    let end = std::time::Instant::now();
+    println!(
+        "Asynchronous label propagation: {} communities in {}",
+        lpa.community_count(),
+        (end - start).as_millis()
+    );
+    partition_report("Asynchronous LPA", &flattened, &lpa);
+
+    let start = std::time::Instant::now();
+    let semi_sync = label_propagation_semi_sync(&flattened, 100);
+    let end = std::time::Instant::now();
+    println!(
+        "Semi-synchronous label propagation: {} communities in {}",
+        semi_sync.community_count(),
+        (end - start).as_millis()
+    );
+    partition_report("Semi-synchronous LPA", &flattened, &semi_sync);
+
+    let start = std::time::Instant::now();
+    let map = infomap(&flattened, config.seed, config.max_levels);
+    let end = std::time::Instant::now();
+    println!(
+        "Infomap: {} communities, codelength {} (one module {}) after {} levels in {}",
+        map.partition.community_count(),
+        map.codelength,
+        map.one_module_codelength,
+        map.levels,
+        (end - start).as_millis()
+    );
+    partition_report("Infomap", &flattened, &map.partition);
+    println!(
+        "Louvain vs Infomap: NMI {}, ARI {}",
+        normalized_mutual_information(&result.partition, &map.partition),
+        adjusted_rand_index(&result.partition, &map.partition)
+    );
+
+    for coupling in [0.0, 0.1, 0.5, 1.0, 5.0] {
+        let config = MultisliceConfig {
+            coupling,
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        let multislice = multislice_louvain(&aucs, &config);
+        let end = std::time::Instant::now();
+        println!(
+            "Multislice omega {}: {} communities, modularity {} after {} levels in {}",
+            coupling,
+            multislice.community_count(),
+            multislice.modularity,
+            multislice.levels,
+            (end - start).as_millis()
+        );
+        for layer in aucs.all_layers() {
+            println!(
+                "  {}: {} communities, NMI with flattened Louvain {}",
+                aucs.layer_name(layer),
+                multislice.memberships[layer].community_count(),
+                normalized_mutual_information(&multislice.memberships[layer], &result.partition)
+            );
+        }
+        let layers = aucs.layer_count();
+        let persistence: Vec<f64> = (0..layers)
+            .flat_map(|a| (a + 1..layers).map(move |b| (a, b)))
+            .map(|(a, b)| multislice.persistence(a, b))
+            .collect();
+        println!(
+            "  average persistence {}",
+            persistence.iter().sum::<f64>() / persistence.len().max(1) as f64
+        );
+    }
+}
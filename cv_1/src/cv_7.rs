@@ -0,0 +1,44 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::diffusion::{diffusion_report, DiffusionModel, EdgeProbability};
+use crate::diffusion_trace::{layout, trace, write_svg_frames, Layout};
+use crate::flattening::{flatten_all, FlatteningWeight};
+use crate::graph::{read_adjacency_list, IndexedGraph};
+use crate::influence::compare_strategies;
+use crate::multilayer::MultilayerGraph;
+
+pub fn cv_7() {
+    let sparse_matrix = read_adjacency_list("socfb-Penn94.txt");
+    let graph = IndexedGraph::new(&sparse_matrix);
+    println!("Graph loaded: {} nodes", graph.len());
+
+    let mut rng = StdRng::seed_from_u64(420);
+    let seed = rng.gen_range(0..graph.len());
+    diffusion_report("penn94", &graph, &[seed], 500, 100);
+
+    let model = DiffusionModel::IndependentCascade(EdgeProbability::Uniform(0.01));
+    compare_strategies("penn94", &graph, model, 5, 100, true);
+
+    // step by step spread with p = 0.5 like cv_7.py, frames only for the small graph
+    let model = DiffusionModel::IndependentCascade(EdgeProbability::Uniform(0.5));
+    let penn_trace = trace(&graph, model, &[seed], 10, &mut rng);
+    penn_trace.write_csv("penn94_trace.csv", &graph);
+    penn_trace.write_jsonl("penn94_trace.jsonl", &graph);
+
+    let aucs = IndexedGraph::new(&flatten_all(
+        &MultilayerGraph::load_aucs("aucs.edges"),
+        FlatteningWeight::Unweighted,
+    ));
+    let aucs_seed = rng.gen_range(0..aucs.len());
+    let aucs_trace = trace(&aucs, model, &[aucs_seed], 10, &mut rng);
+    aucs_trace.write_jsonl("aucs_trace.jsonl", &aucs);
+    let positions = layout(
+        &aucs,
+        Layout::Spring {
+            iterations: 200,
+            seed: 1,
+        },
+    );
+    write_svg_frames("visualization_aucs", &aucs, &aucs_trace, &positions);
+}
@@ -0,0 +1,354 @@
+use std::fs::write;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use crate::graph::IndexedGraph;
+
+/// Activation probability of the edge `u -> v` in the independent cascade.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeProbability {
+    Uniform(f64),
+    // 1 / degree(v), every node expects one successful activation from its neighbors
+    WeightedCascade,
+    // 1 - (1 - p)^w, an edge of weight w acts like w independent trials of p
+    Multiplicity(f64),
+}
+
+impl EdgeProbability {
+    pub fn probability(&self, graph: &IndexedGraph, v: usize, weight: f64) -> f64 {
+        match *self {
+            EdgeProbability::Uniform(p) => p,
+            EdgeProbability::WeightedCascade => 1.0 / graph.degree(v).max(1) as f64,
+            EdgeProbability::Multiplicity(p) => 1.0 - (1.0 - p).powf(weight),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffusionModel {
+    IndependentCascade(EdgeProbability),
+    // Thresholds drawn uniformly per trial, influence of u on v is w_uv / s_v
+    LinearThreshold,
+    Si { beta: f64 },
+    Sir { beta: f64, gamma: f64 },
+    Sis { beta: f64, gamma: f64 },
+}
+
+impl DiffusionModel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiffusionModel::IndependentCascade(_) => "independent cascade",
+            DiffusionModel::LinearThreshold => "linear threshold",
+            DiffusionModel::Si { .. } => "SI",
+            DiffusionModel::Sir { .. } => "SIR",
+            DiffusionModel::Sis { .. } => "SIS",
+        }
+    }
+}
+
+/// For the cascades `Infected` marks the nodes activated in the last step (the ones still trying
+/// to influence their neighbors, `influencing_nodes` in `cv_7.py`) and `Recovered` the nodes
+/// activated before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeState {
+    Susceptible,
+    Infected,
+    Recovered,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StepCounts {
+    pub susceptible: usize,
+    pub infected: usize,
+    pub recovered: usize,
+    // Nodes that became infected (activated) in this step
+    pub new: usize,
+}
+
+impl StepCounts {
+    /// Nodes ever activated, the spread of a cascade.
+    pub fn active(&self) -> usize {
+        self.infected + self.recovered
+    }
+}
+
+fn counts(states: &[NodeState], new: usize) -> StepCounts {
+    let mut result = StepCounts {
+        new,
+        ..Default::default()
+    };
+    for state in states {
+        match state {
+            NodeState::Susceptible => result.susceptible += 1,
+            NodeState::Infected => result.infected += 1,
+            NodeState::Recovered => result.recovered += 1,
+        }
+    }
+    result
+}
+
+/// One trial from `seeds` (node indices of `graph`). Step 0 is the seeded state, the trial ends
+/// once nothing can change anymore or after `max_steps` steps. `observe` sees the node states
+/// after every step.
+pub fn simulate_observed<R, F>(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    seeds: &[usize],
+    max_steps: usize,
+    rng: &mut R,
+    mut observe: F,
+) -> Vec<StepCounts>
+where
+    R: Rng,
+    F: FnMut(usize, &[NodeState]),
+{
+    let n = graph.len();
+    let mut states = vec![NodeState::Susceptible; n];
+    for &seed in seeds {
+        states[seed] = NodeState::Infected;
+    }
+    let thresholds: Vec<f64> = match model {
+        DiffusionModel::LinearThreshold => (0..n).map(|_| rng.gen::<f64>()).collect(),
+        _ => Vec::new(),
+    };
+    let strengths: Vec<f64> = (0..n).map(|node| graph.strength(node)).collect();
+    // accumulated influence for the linear threshold model
+    let mut influence = vec![0.0; n];
+
+    let mut current = counts(&states, seeds.len());
+    let mut history = vec![current];
+    observe(0, &states);
+
+    // the infected nodes and the running counts are kept up to date instead of rescanning all
+    // nodes every step
+    let mut infected: Vec<usize> = (0..n)
+        .filter(|&node| states[node] == NodeState::Infected)
+        .collect();
+    let mut left = vec![false; n];
+    for step in 1..=max_steps {
+        if infected.is_empty() {
+            break;
+        }
+
+        let mut newly: Vec<usize> = Vec::new();
+        let mut leaving: Vec<(usize, NodeState)> = Vec::new();
+        match model {
+            DiffusionModel::IndependentCascade(probability) => {
+                for &u in infected.iter() {
+                    for &(v, weight) in graph.neighbors[u].iter() {
+                        if states[v] == NodeState::Susceptible
+                            && rng.gen::<f64>() < probability.probability(graph, v, weight)
+                        {
+                            newly.push(v);
+                        }
+                    }
+                    leaving.push((u, NodeState::Recovered));
+                }
+            }
+            DiffusionModel::LinearThreshold => {
+                for &u in infected.iter() {
+                    for &(v, weight) in graph.neighbors[u].iter() {
+                        if states[v] != NodeState::Susceptible || strengths[v] == 0.0 {
+                            continue;
+                        }
+                        let before = influence[v];
+                        influence[v] += weight / strengths[v];
+                        if before < thresholds[v] && influence[v] >= thresholds[v] {
+                            newly.push(v);
+                        }
+                    }
+                    leaving.push((u, NodeState::Recovered));
+                }
+            }
+            DiffusionModel::Si { beta }
+            | DiffusionModel::Sir { beta, .. }
+            | DiffusionModel::Sis { beta, .. } => {
+                for &u in infected.iter() {
+                    for &(v, _) in graph.neighbors[u].iter() {
+                        if states[v] == NodeState::Susceptible && rng.gen::<f64>() < beta {
+                            newly.push(v);
+                        }
+                    }
+                }
+                let recovery = match model {
+                    DiffusionModel::Sir { gamma, .. } => Some((gamma, NodeState::Recovered)),
+                    DiffusionModel::Sis { gamma, .. } => Some((gamma, NodeState::Susceptible)),
+                    _ => None,
+                };
+                if let Some((gamma, next)) = recovery {
+                    for &u in infected.iter() {
+                        if rng.gen::<f64>() < gamma {
+                            leaving.push((u, next));
+                        }
+                    }
+                }
+            }
+        }
+
+        for &(u, state) in leaving.iter() {
+            states[u] = state;
+            left[u] = true;
+            current.infected -= 1;
+            match state {
+                NodeState::Susceptible => current.susceptible += 1,
+                _ => current.recovered += 1,
+            }
+        }
+        let mut next: Vec<usize> = infected.into_iter().filter(|&u| !left[u]).collect();
+        current.new = 0;
+        for v in newly {
+            if states[v] == NodeState::Susceptible {
+                states[v] = NodeState::Infected;
+                current.susceptible -= 1;
+                current.infected += 1;
+                current.new += 1;
+                next.push(v);
+            }
+        }
+        for &(u, _) in leaving.iter() {
+            left[u] = false;
+        }
+        infected = next;
+        history.push(current);
+        observe(step, &states);
+    }
+
+    history
+}
+
+pub fn simulate<R: Rng>(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    seeds: &[usize],
+    max_steps: usize,
+    rng: &mut R,
+) -> Vec<StepCounts> {
+    simulate_observed(graph, model, seeds, max_steps, rng, |_, _| {})
+}
+
+#[derive(Debug, Clone)]
+pub struct MonteCarloResult {
+    pub trials: Vec<Vec<StepCounts>>,
+}
+
+impl MonteCarloResult {
+    /// Average counts per step, trials that ended early keep their final state.
+    pub fn mean_counts(&self) -> Vec<(f64, f64, f64, f64)> {
+        let steps = self.trials.iter().map(|t| t.len()).max().unwrap_or(0);
+        let n = self.trials.len() as f64;
+        (0..steps)
+            .map(|step| {
+                let mut sum = (0, 0, 0, 0);
+                for trial in self.trials.iter() {
+                    let counts = trial[step.min(trial.len() - 1)];
+                    sum.0 += counts.susceptible;
+                    sum.1 += counts.infected;
+                    sum.2 += counts.recovered;
+                    if step < trial.len() {
+                        sum.3 += counts.new;
+                    }
+                }
+                (
+                    sum.0 as f64 / n,
+                    sum.1 as f64 / n,
+                    sum.2 as f64 / n,
+                    sum.3 as f64 / n,
+                )
+            })
+            .collect()
+    }
+
+    /// Average number of infected or recovered nodes at the end of a trial, the expected spread
+    /// of a cascade. For SIS, where nodes can be infected repeatedly, it is the final prevalence.
+    pub fn mean_spread(&self) -> f64 {
+        if self.trials.is_empty() {
+            return 0.0;
+        }
+        self.trials
+            .iter()
+            .map(|trial| trial.last().unwrap().active())
+            .sum::<usize>() as f64
+            / self.trials.len() as f64
+    }
+}
+
+/// Runs `trials` independent trials in parallel, trial `i` uses the seed `seed + i` so the result
+/// does not depend on the number of threads.
+pub fn monte_carlo(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    seeds: &[usize],
+    max_steps: usize,
+    trials: usize,
+    seed: u64,
+) -> MonteCarloResult {
+    let trials = (0..trials)
+        .into_par_iter()
+        .map(|i| {
+            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+            simulate(graph, model, seeds, max_steps, &mut rng)
+        })
+        .collect();
+    MonteCarloResult { trials }
+}
+
+/// Lines `step,susceptible,infected,recovered,new` of the mean counts.
+pub fn write_mean_counts(path: &str, result: &MonteCarloResult) {
+    let mut lines = vec![String::from("step,susceptible,infected,recovered,new")];
+    for (step, (s, i, r, new)) in result.mean_counts().iter().enumerate() {
+        lines.push(format!("{},{},{},{},{}", step, s, i, r, new));
+    }
+    write(path, lines.join("\n").as_bytes()).unwrap();
+}
+
+pub fn diffusion_report(
+    name: &str,
+    graph: &IndexedGraph,
+    seeds: &[usize],
+    max_steps: usize,
+    trials: usize,
+) {
+    let models = [
+        (
+            "ic",
+            DiffusionModel::IndependentCascade(EdgeProbability::Uniform(0.01)),
+        ),
+        (
+            "ic_wc",
+            DiffusionModel::IndependentCascade(EdgeProbability::WeightedCascade),
+        ),
+        ("lt", DiffusionModel::LinearThreshold),
+        ("si", DiffusionModel::Si { beta: 0.01 }),
+        (
+            "sir",
+            DiffusionModel::Sir {
+                beta: 0.01,
+                gamma: 0.1,
+            },
+        ),
+        (
+            "sis",
+            DiffusionModel::Sis {
+                beta: 0.01,
+                gamma: 0.1,
+            },
+        ),
+    ];
+    for (short, model) in models {
+        let start = std::time::Instant::now();
+        let result = monte_carlo(graph, model, seeds, max_steps, trials, 420);
+        let end = std::time::Instant::now();
+        println!(
+            "{} {}: mean spread {} of {} nodes over {} trials in {}",
+            name,
+            model.name(),
+            result.mean_spread(),
+            graph.len(),
+            trials,
+            (end - start).as_millis()
+        );
+        write_mean_counts(&format!("{}_{}.csv", name, short), &result);
+    }
+}
@@ -0,0 +1,284 @@
+use std::fs::{create_dir_all, write};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::diffusion::{simulate_observed, DiffusionModel, NodeState};
+use crate::graph::IndexedGraph;
+
+fn state_name(state: NodeState) -> &'static str {
+    match state {
+        NodeState::Susceptible => "susceptible",
+        NodeState::Infected => "infected",
+        NodeState::Recovered => "recovered",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StateChange {
+    pub step: usize,
+    // Node index of the traced graph
+    pub node: usize,
+    pub from: NodeState,
+    pub to: NodeState,
+}
+
+/// Every node state change of one trial. Step 0 holds the seeding, so the states after any step
+/// can be replayed from an all-susceptible start.
+#[derive(Debug, Clone)]
+pub struct DiffusionTrace {
+    pub seeds: Vec<usize>,
+    pub steps: usize,
+    pub changes: Vec<StateChange>,
+}
+
+impl DiffusionTrace {
+    /// Node states after `step`.
+    pub fn states_at(&self, n: usize, step: usize) -> Vec<NodeState> {
+        let mut states = vec![NodeState::Susceptible; n];
+        for change in self.changes.iter().take_while(|c| c.step <= step) {
+            states[change.node] = change.to;
+        }
+        states
+    }
+
+    /// Lines `step,node,from,to` with node ids of `graph`.
+    pub fn write_csv(&self, path: &str, graph: &IndexedGraph) {
+        let mut lines = vec![String::from("step,node,from,to")];
+        for change in self.changes.iter() {
+            lines.push(format!(
+                "{},{},{},{}",
+                change.step,
+                graph.ids[change.node],
+                state_name(change.from),
+                state_name(change.to)
+            ));
+        }
+        write(path, lines.join("\n").as_bytes()).unwrap();
+    }
+
+    /// One JSON object per step with the node ids that entered each state.
+    pub fn write_jsonl(&self, path: &str, graph: &IndexedGraph) {
+        let mut lines = Vec::with_capacity(self.steps + 1);
+        let mut changes = self.changes.iter().peekable();
+        for step in 0..=self.steps {
+            let mut entered: [Vec<String>; 3] = [Vec::new(), Vec::new(), Vec::new()];
+            while let Some(change) = changes.next_if(|c| c.step == step) {
+                let slot = match change.to {
+                    NodeState::Susceptible => 0,
+                    NodeState::Infected => 1,
+                    NodeState::Recovered => 2,
+                };
+                entered[slot].push(graph.ids[change.node].to_string());
+            }
+            lines.push(format!(
+                "{{\"step\":{},\"susceptible\":[{}],\"infected\":[{}],\"recovered\":[{}]}}",
+                step,
+                entered[0].join(","),
+                entered[1].join(","),
+                entered[2].join(",")
+            ));
+        }
+        write(path, lines.join("\n").as_bytes()).unwrap();
+    }
+}
+
+/// Runs one trial and records the state changes between consecutive steps.
+pub fn trace<R: Rng>(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    seeds: &[usize],
+    max_steps: usize,
+    rng: &mut R,
+) -> DiffusionTrace {
+    let mut previous = vec![NodeState::Susceptible; graph.len()];
+    let mut changes = Vec::new();
+    let history = simulate_observed(graph, model, seeds, max_steps, rng, |step, states| {
+        for (node, (&before, &after)) in previous.iter().zip(states.iter()).enumerate() {
+            if before != after {
+                changes.push(StateChange {
+                    step,
+                    node,
+                    from: before,
+                    to: after,
+                });
+            }
+        }
+        previous.copy_from_slice(states);
+    });
+    DiffusionTrace {
+        seeds: seeds.to_vec(),
+        steps: history.len() - 1,
+        changes,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    Circular,
+    // Fruchterman-Reingold, O(n^2) per iteration, meant for small graphs
+    Spring { iterations: usize, seed: u64 },
+}
+
+/// Node positions in the unit square, fixed so every frame of a trace uses the same drawing.
+pub fn layout(graph: &IndexedGraph, layout: Layout) -> Vec<(f64, f64)> {
+    let n = graph.len();
+    let circle: Vec<(f64, f64)> = (0..n)
+        .map(|i| {
+            let angle = 2.0 * std::f64::consts::PI * i as f64 / n.max(1) as f64;
+            (0.5 + 0.5 * angle.cos(), 0.5 + 0.5 * angle.sin())
+        })
+        .collect();
+    let (iterations, seed) = match layout {
+        Layout::Circular => return circle,
+        Layout::Spring { iterations, seed } => (iterations, seed),
+    };
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut positions: Vec<(f64, f64)> = (0..n).map(|_| (rng.gen(), rng.gen())).collect();
+    let k = (1.0 / n.max(1) as f64).sqrt();
+    let mut temperature = 0.1;
+    let cooling = temperature / (iterations + 1) as f64;
+    for _ in 0..iterations {
+        let mut displacement = vec![(0.0, 0.0); n];
+        for i in 0..n {
+            for j in 0..n {
+                if i == j {
+                    continue;
+                }
+                let dx = positions[i].0 - positions[j].0;
+                let dy = positions[i].1 - positions[j].1;
+                let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+                let force = k * k / distance;
+                displacement[i].0 += dx / distance * force;
+                displacement[i].1 += dy / distance * force;
+            }
+            for &(j, _) in graph.neighbors[i].iter() {
+                let dx = positions[i].0 - positions[j].0;
+                let dy = positions[i].1 - positions[j].1;
+                let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+                let force = distance * distance / k;
+                displacement[i].0 -= dx / distance * force;
+                displacement[i].1 -= dy / distance * force;
+            }
+        }
+        for (position, (dx, dy)) in positions.iter_mut().zip(displacement) {
+            let length = (dx * dx + dy * dy).sqrt().max(0.01);
+            position.0 += dx / length * length.min(temperature);
+            position.1 += dy / length * length.min(temperature);
+        }
+        temperature -= cooling;
+    }
+
+    // rescale into the unit square
+    let min_x = positions.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let max_x = positions
+        .iter()
+        .map(|p| p.0)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let min_y = positions.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_y = positions
+        .iter()
+        .map(|p| p.1)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let scale = (max_x - min_x).max(max_y - min_y).max(1e-9);
+    positions
+        .into_iter()
+        .map(|(x, y)| ((x - min_x) / scale, (y - min_y) / scale))
+        .collect()
+}
+
+/// Colors of `cv_7.py`: red for the influencing nodes, orange for the initial ones, green for the
+/// influenced ones and blue for the rest.
+fn color(state: NodeState, initial: bool) -> &'static str {
+    match state {
+        NodeState::Infected => "red",
+        _ if initial => "orange",
+        NodeState::Recovered => "green",
+        NodeState::Susceptible => "blue",
+    }
+}
+
+pub fn render_svg(
+    graph: &IndexedGraph,
+    positions: &[(f64, f64)],
+    states: &[NodeState],
+    seeds: &[usize],
+    size: f64,
+    title: &str,
+) -> String {
+    let margin = 20.0;
+    let scale = size - 2.0 * margin;
+    let point = |node: usize| {
+        (
+            margin + positions[node].0 * scale,
+            margin + positions[node].1 * scale,
+        )
+    };
+    let mut lines = vec![format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        size, size
+    )];
+    lines.push(format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>",
+        size, size
+    ));
+    lines.push(format!(
+        "<text x=\"5\" y=\"15\" font-size=\"12\">{}</text>",
+        title
+    ));
+    lines.push(String::from("<g stroke=\"#999\" stroke-width=\"0.5\">"));
+    for (a, neighbors) in graph.neighbors.iter().enumerate() {
+        for &(b, _) in neighbors.iter().filter(|e| e.0 > a) {
+            let (x1, y1) = point(a);
+            let (x2, y2) = point(b);
+            lines.push(format!(
+                "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>",
+                x1, y1, x2, y2
+            ));
+        }
+    }
+    lines.push(String::from("</g>"));
+    let mut initial = vec![false; graph.len()];
+    for &seed in seeds {
+        initial[seed] = true;
+    }
+    for (node, &state) in states.iter().enumerate() {
+        let (x, y) = point(node);
+        lines.push(format!(
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"4\" fill=\"{}\"><title>{}</title></circle>",
+            x,
+            y,
+            color(state, initial[node]),
+            graph.ids[node]
+        ));
+    }
+    lines.push(String::from("</svg>"));
+    lines.join("\n")
+}
+
+/// Writes `directory/step_N.svg` for every step of the trace.
+pub fn write_svg_frames(
+    directory: &str,
+    graph: &IndexedGraph,
+    trace: &DiffusionTrace,
+    positions: &[(f64, f64)],
+) {
+    create_dir_all(directory).unwrap();
+    let mut states = vec![NodeState::Susceptible; graph.len()];
+    let mut changes = trace.changes.iter().peekable();
+    for step in 0..=trace.steps {
+        while let Some(change) = changes.next_if(|c| c.step == step) {
+            states[change.node] = change.to;
+        }
+        let svg = render_svg(
+            graph,
+            positions,
+            &states,
+            &trace.seeds,
+            800.0,
+            &format!("Step {}", step),
+        );
+        write(format!("{}/step_{}.svg", directory, step), svg.as_bytes()).unwrap();
+    }
+}
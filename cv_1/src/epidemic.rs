@@ -0,0 +1,209 @@
+use std::fs::write;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+use crate::diffusion::{simulate, DiffusionModel};
+use crate::functions::degree_distribution;
+use crate::graph::{read_edge_list, symmetrize, IndexedGraph};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Epidemic {
+    Sir,
+    Sis,
+}
+
+impl Epidemic {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Epidemic::Sir => "SIR",
+            Epidemic::Sis => "SIS",
+        }
+    }
+
+    pub fn model(&self, beta: f64, gamma: f64) -> DiffusionModel {
+        match self {
+            Epidemic::Sir => DiffusionModel::Sir { beta, gamma },
+            Epidemic::Sis => DiffusionModel::Sis { beta, gamma },
+        }
+    }
+}
+
+/// First and second moment `(<k>, <k^2>)` of a degree distribution.
+pub fn degree_moments(distribution: &HashMap<usize, usize>) -> (f64, f64) {
+    let nodes = distribution.values().sum::<usize>().max(1) as f64;
+    let mut k = 0.0;
+    let mut k2 = 0.0;
+    for (&degree, &count) in distribution.iter() {
+        k += (degree * count) as f64;
+        k2 += (degree * degree * count) as f64;
+    }
+    (k / nodes, k2 / nodes)
+}
+
+/// Critical transmissibility `<k> / (<k^2> - <k>)` of an uncorrelated network with the given
+/// degree distribution, the percolation threshold of SIR.
+pub fn epidemic_threshold(distribution: &HashMap<usize, usize>) -> f64 {
+    let (k, k2) = degree_moments(distribution);
+    k / (k2 - k)
+}
+
+/// Infection probability per step at which the predicted threshold is reached. In the discrete
+/// SIR an infected node tries every step until it recovers, so an edge transmits with
+/// `T = beta / (1 - (1 - beta)(1 - gamma))`; SIS uses the mean-field `beta / gamma = <k> / <k^2>`.
+pub fn predicted_beta(distribution: &HashMap<usize, usize>, epidemic: Epidemic, gamma: f64) -> f64 {
+    match epidemic {
+        Epidemic::Sir => {
+            let t = epidemic_threshold(distribution);
+            (t * gamma / (1.0 - t * (1.0 - gamma))).clamp(0.0, 1.0)
+        }
+        Epidemic::Sis => {
+            let (k, k2) = degree_moments(distribution);
+            (gamma * k / k2).clamp(0.0, 1.0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepPoint {
+    pub beta: f64,
+    // Final share of infected or recovered nodes, the final prevalence for SIS
+    pub mean_size: f64,
+    pub variance: f64,
+    // sqrt(variance) / mean, peaks at the SIR threshold (Shu et al.)
+    pub variability: f64,
+    // n * variance / mean, peaks at the SIS threshold (Ferreira et al.)
+    pub susceptibility: f64,
+}
+
+/// Final outbreak sizes for every `beta`, each trial starts from one random node. Trial `i` uses
+/// the seed `seed + i` for every `beta`, so neighboring values share their random numbers.
+pub fn epidemic_sweep(
+    graph: &IndexedGraph,
+    epidemic: Epidemic,
+    betas: &[f64],
+    gamma: f64,
+    trials: usize,
+    max_steps: usize,
+    seed: u64,
+) -> Vec<SweepPoint> {
+    let n = graph.len() as f64;
+    betas
+        .iter()
+        .map(|&beta| {
+            let model = epidemic.model(beta, gamma);
+            let sizes: Vec<f64> = (0..trials)
+                .into_par_iter()
+                .map(|i| {
+                    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+                    let start = rng.gen_range(0..graph.len());
+                    let history = simulate(graph, model, &[start], max_steps, &mut rng);
+                    history.last().unwrap().active() as f64 / n
+                })
+                .collect();
+            let count = sizes.len().max(1) as f64;
+            let mean_size = sizes.iter().sum::<f64>() / count;
+            let variance = sizes.iter().map(|s| (s - mean_size).powi(2)).sum::<f64>() / count;
+            let (variability, susceptibility) = if mean_size > 0.0 {
+                (variance.sqrt() / mean_size, n * variance / mean_size)
+            } else {
+                (0.0, 0.0)
+            };
+            SweepPoint {
+                beta,
+                mean_size,
+                variance,
+                variability,
+                susceptibility,
+            }
+        })
+        .collect()
+}
+
+/// `beta` where the threshold measure of the epidemic peaks.
+pub fn empirical_threshold(points: &[SweepPoint], epidemic: Epidemic) -> Option<f64> {
+    let measure = |p: &SweepPoint| match epidemic {
+        Epidemic::Sir => p.variability,
+        Epidemic::Sis => p.susceptibility,
+    };
+    points
+        .iter()
+        .max_by(|a, b| measure(a).partial_cmp(&measure(b)).unwrap())
+        .map(|p| p.beta)
+}
+
+/// Lines `beta,mean,variance,variability,susceptibility`.
+pub fn write_sweep(path: &str, points: &[SweepPoint]) {
+    let mut lines = vec![String::from(
+        "beta,mean,variance,variability,susceptibility",
+    )];
+    for p in points {
+        lines.push(format!(
+            "{},{},{},{},{}",
+            p.beta, p.mean_size, p.variance, p.variability, p.susceptibility
+        ));
+    }
+    write(path, lines.join("\n").as_bytes()).unwrap();
+}
+
+/// Sweeps `beta` over a log grid around the predicted threshold and compares the empirical
+/// threshold with the degree-based prediction.
+pub fn epidemic_threshold_report(
+    name: &str,
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    gamma: f64,
+    trials: usize,
+    max_steps: usize,
+) {
+    let distribution = degree_distribution(sparse_matrix);
+    let (k, k2) = degree_moments(&distribution);
+    println!(
+        "{}: <k> {}, <k^2> {}, predicted threshold <k>/(<k^2>-<k>) {}",
+        name,
+        k,
+        k2,
+        epidemic_threshold(&distribution)
+    );
+    let graph = IndexedGraph::new(sparse_matrix);
+
+    for epidemic in [Epidemic::Sir, Epidemic::Sis] {
+        let predicted = predicted_beta(&distribution, epidemic, gamma);
+        // 17 values from predicted / 10 to predicted * 10
+        let betas: Vec<f64> = (0..=16)
+            .map(|i| (predicted * 10f64.powf(-1.0 + i as f64 / 8.0)).min(1.0))
+            .collect();
+        let start = std::time::Instant::now();
+        let points = epidemic_sweep(&graph, epidemic, &betas, gamma, trials, max_steps, 420);
+        let end = std::time::Instant::now();
+        for p in points.iter() {
+            println!(
+                "{} {} beta {:.6}: size {:.6} variance {:.6}",
+                name,
+                epidemic.name(),
+                p.beta,
+                p.mean_size,
+                p.variance
+            );
+        }
+        println!(
+            "{} {}: predicted beta {:.6}, empirical beta {:.6} in {}",
+            name,
+            epidemic.name(),
+            predicted,
+            empirical_threshold(&points, epidemic).unwrap_or(0.0),
+            (end - start).as_millis()
+        );
+        write_sweep(
+            &format!("{}_{}_sweep.csv", name, epidemic.name().to_lowercase()),
+            &points,
+        );
+    }
+}
+
+pub fn sweep() {
+    let sparse_matrix = symmetrize(&read_edge_list("com-youtube.ungraph.txt"));
+    println!("Graph loaded: {} nodes", sparse_matrix.len());
+    epidemic_threshold_report("youtube", &sparse_matrix, 0.1, 50, 500);
+}
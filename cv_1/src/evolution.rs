@@ -0,0 +1,229 @@
+use std::fs::write;
+
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon_hash::{HashMap, HashSet};
+
+use crate::functions::get_cl_coef;
+use crate::graph::{bfs_distances, connected_components, edge_count, symmetrize};
+
+// Number of BFS sources used to estimate the average shortest path length of the giant component.
+const PATH_SAMPLES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvolutionMode {
+    // Only the papers published in the given year
+    Snapshot,
+    // Everything published up to and including the given year
+    Cumulative,
+}
+
+impl EvolutionMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EvolutionMode::Snapshot => "snapshot",
+            EvolutionMode::Cumulative => "cumulative",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct YearMetrics {
+    pub year: u32,
+    pub nodes: usize,
+    pub edges: usize,
+    pub new_nodes: usize,
+    pub new_edges: usize,
+    pub density: f64,
+    pub avg_degree: f64,
+    pub avg_strength: f64,
+    pub clustering: f64,
+    pub giant_component: usize,
+    pub avg_path_length: f64,
+}
+
+/// Average shortest path length inside the giant component, estimated from BFS runs started at
+/// evenly spaced nodes of the (sorted) component.
+pub fn estimate_avg_path_length(
+    adjacency: &HashMap<usize, HashMap<usize, usize>>,
+    component: &[usize],
+    samples: usize,
+) -> f64 {
+    if component.len() < 2 || samples == 0 {
+        return 0.0;
+    }
+    let step = (component.len() / samples).max(1);
+    let sources: Vec<usize> = component
+        .iter()
+        .step_by(step)
+        .take(samples)
+        .cloned()
+        .collect();
+
+    let (total, count) = sources
+        .par_iter()
+        .map(|&source| {
+            let distances = bfs_distances(adjacency, source);
+            let total: usize = distances.values().sum();
+            (total, distances.len() - 1)
+        })
+        .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
+
+fn compute_year_metrics(
+    year: u32,
+    adjacency: &HashMap<usize, HashMap<usize, usize>>,
+    new_nodes: usize,
+    new_edges: usize,
+) -> YearMetrics {
+    let nodes = adjacency.len();
+    let edges = edge_count(adjacency);
+    let strength: usize = adjacency.values().flat_map(|v| v.values()).sum();
+
+    let nodes_vec: Vec<usize> = adjacency.keys().cloned().collect();
+    let clustering_sum: f64 = nodes_vec
+        .par_iter()
+        .map(|&node| get_cl_coef(adjacency, node))
+        .sum();
+
+    let components = connected_components(adjacency);
+    let giant = components.first().cloned().unwrap_or_default();
+
+    let (density, avg_degree, avg_strength, clustering) = if nodes == 0 {
+        (0.0, 0.0, 0.0, 0.0)
+    } else {
+        let density = if nodes > 1 {
+            2.0 * edges as f64 / (nodes as f64 * (nodes as f64 - 1.0))
+        } else {
+            0.0
+        };
+        (
+            density,
+            2.0 * edges as f64 / nodes as f64,
+            strength as f64 / nodes as f64,
+            clustering_sum / nodes as f64,
+        )
+    };
+
+    YearMetrics {
+        year,
+        nodes,
+        edges,
+        new_nodes,
+        new_edges,
+        density,
+        avg_degree,
+        avg_strength,
+        clustering,
+        giant_component: giant.len(),
+        avg_path_length: estimate_avg_path_length(adjacency, &giant, PATH_SAMPLES),
+    }
+}
+
+/// Time series of network metrics for every year in the data, sorted by year. New nodes and new
+/// edges are the ones that never appeared in any earlier year, regardless of the mode.
+pub fn compute_evolution(
+    data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>,
+    mode: EvolutionMode,
+) -> Vec<YearMetrics> {
+    let mut years: Vec<u32> = data.keys().cloned().collect();
+    years.sort_unstable();
+
+    let mut seen_nodes: HashSet<usize> = HashSet::new();
+    let mut seen_edges: HashSet<(usize, usize)> = HashSet::new();
+    let mut cumulative: HashMap<usize, HashMap<usize, usize>> = HashMap::new();
+    let mut result = Vec::with_capacity(years.len());
+
+    for year in years {
+        let year_matrix = &data[&year];
+
+        let mut new_nodes = 0;
+        let mut new_edges = 0;
+        for (&author1, links) in year_matrix.iter() {
+            if seen_nodes.insert(author1) {
+                new_nodes += 1;
+            }
+            for &author2 in links.keys() {
+                if seen_nodes.insert(author2) {
+                    new_nodes += 1;
+                }
+                if seen_edges.insert((author1.min(author2), author1.max(author2))) {
+                    new_edges += 1;
+                }
+            }
+        }
+
+        let metrics = match mode {
+            EvolutionMode::Snapshot => {
+                compute_year_metrics(year, &symmetrize(year_matrix), new_nodes, new_edges)
+            }
+            EvolutionMode::Cumulative => {
+                for (&author1, links) in year_matrix.iter() {
+                    for (&author2, &weight) in links.iter() {
+                        if author1 == author2 {
+                            continue;
+                        }
+                        *cumulative
+                            .entry(author1)
+                            .or_default()
+                            .entry(author2)
+                            .or_insert(0) += weight;
+                        *cumulative
+                            .entry(author2)
+                            .or_default()
+                            .entry(author1)
+                            .or_insert(0) += weight;
+                    }
+                }
+                compute_year_metrics(year, &cumulative, new_nodes, new_edges)
+            }
+        };
+
+        result.push(metrics);
+    }
+
+    result
+}
+
+pub fn write_evolution_csv(path: &str, metrics: &[YearMetrics]) {
+    let mut lines = vec![String::from(
+        "year,nodes,edges,new_nodes,new_edges,density,avg_degree,avg_strength,clustering,giant_component,avg_path_length",
+    )];
+    for m in metrics {
+        lines.push(format!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            m.year,
+            m.nodes,
+            m.edges,
+            m.new_nodes,
+            m.new_edges,
+            m.density,
+            m.avg_degree,
+            m.avg_strength,
+            m.clustering,
+            m.giant_component,
+            m.avg_path_length
+        ));
+    }
+    write(path, lines.join("\n").as_bytes()).unwrap();
+}
+
+pub fn evolution_report(data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>) {
+    for mode in [EvolutionMode::Snapshot, EvolutionMode::Cumulative] {
+        let start = std::time::Instant::now();
+        let metrics = compute_evolution(data, mode);
+        write_evolution_csv(&format!("evolution_{}.csv", mode.name()), &metrics);
+        let end = std::time::Instant::now();
+        println!(
+            "Evolution {} ({} years) in {}",
+            mode.name(),
+            metrics.len(),
+            (end - start).as_millis()
+        );
+    }
+}
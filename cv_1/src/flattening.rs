@@ -0,0 +1,63 @@
+use rayon_hash::HashMap;
+
+use crate::multilayer::MultilayerGraph;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlatteningWeight {
+    // Every merged edge gets weight 1
+    Unweighted,
+    // Number of layers the edge appears in
+    LayerCount,
+    // Sum of the edge weights over the layers
+    WeightSum,
+}
+
+/// Merges `layers` into a single sparse matrix with the same layout as `cv_1::cv_1`, so it can be
+/// passed to the functions in `functions.rs` and `functions_par.rs`. Edges are kept in both
+/// directions and node ids stay the ones of `graph`.
+pub fn flatten(
+    graph: &MultilayerGraph,
+    layers: &[usize],
+    weighting: FlatteningWeight,
+) -> HashMap<usize, HashMap<usize, usize>> {
+    let mut result: HashMap<usize, HashMap<usize, usize>> = HashMap::new();
+    for &layer in layers {
+        for (&node, neighbors) in graph.layer(layer).iter() {
+            let entry = result.entry(node).or_default();
+            for (&neighbor, &weight) in neighbors.iter() {
+                let value = entry.entry(neighbor).or_insert(0);
+                match weighting {
+                    FlatteningWeight::Unweighted => *value = 1,
+                    FlatteningWeight::LayerCount => *value += 1,
+                    FlatteningWeight::WeightSum => *value += weight,
+                }
+            }
+        }
+    }
+    result
+}
+
+pub fn flatten_all(
+    graph: &MultilayerGraph,
+    weighting: FlatteningWeight,
+) -> HashMap<usize, HashMap<usize, usize>> {
+    flatten(graph, &graph.all_layers(), weighting)
+}
+
+/// Copy of `graph` with only `layers`. Node ids are preserved, layer ids follow the order of
+/// `layers`.
+pub fn restrict_layers(graph: &MultilayerGraph, layers: &[usize]) -> MultilayerGraph {
+    let mut result = MultilayerGraph::new();
+    for node in 0..graph.node_count() {
+        result.add_node(graph.node_label(node));
+    }
+    for &layer in layers {
+        let new_layer = result.add_layer(graph.layer_name(layer));
+        for (&node, neighbors) in graph.layer(layer).iter() {
+            for (&neighbor, &weight) in neighbors.iter() {
+                result.add_edge(new_layer, node, neighbor, weight);
+            }
+        }
+    }
+    result
+}
@@ -31,8 +31,10 @@ pub fn get_max_dg(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>) {
     );
 }
 
-pub fn get_dg_dis(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>) {
-    let start = std::time::Instant::now();
+/// Degree -> number of nodes with that degree.
+pub fn degree_distribution(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+) -> HashMap<usize, usize> {
     let mut degree_distribution: HashMap<usize, usize> = HashMap::new();
     for (_, v) in sparse_matrix.iter() {
         degree_distribution
@@ -40,6 +42,12 @@ pub fn get_dg_dis(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>) {
             .and_modify(|e| *e += 1)
             .or_insert(1);
     }
+    degree_distribution
+}
+
+pub fn get_dg_dis(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>) {
+    let start = std::time::Instant::now();
+    degree_distribution(sparse_matrix);
     let end = std::time::Instant::now();
     println!("Degree distribution in {}", (end - start).as_millis());
 }
@@ -94,7 +102,7 @@ pub fn get_cl_ds(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>) {
     println!("Clustering distribution in {}", (end - start).as_millis());
 }
 
-fn get_cl_coef(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>, node: usize) -> f64 {
+pub fn get_cl_coef(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>, node: usize) -> f64 {
     let neighbors = match sparse_matrix.get(&node) {
         Some(neigh) => neigh.keys().collect::<Vec<&usize>>(),
         None => return 0.0,
@@ -136,7 +144,7 @@ pub fn get_cl_ef_dis(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>) {
             .push(*coeff);
     }
 
-    let distribution_vec: Vec<(usize, f64)> = degree_to_coefficients
+    let _distribution_vec: Vec<(usize, f64)> = degree_to_coefficients
         .into_iter()
         .map(|(degree, coeffs)| {
             let avg_coeff = coeffs.iter().sum::<f64>() / coeffs.len() as f64;
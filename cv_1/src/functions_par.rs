@@ -51,11 +51,9 @@ pub fn get_dg_dis_par(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>) {
     let end = std::time::Instant::now();
     println!("Degree distribution par in {}", (end - start).as_millis());
 
-    let mut degree_distribution_vec: Vec<(usize, usize)> = degree_distribution
-        .into_iter()
-        .map(|(k, v)| (k, v))
-        .collect();
-    degree_distribution_vec.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut degree_distribution_vec: Vec<(usize, usize)> =
+        degree_distribution.into_iter().collect();
+    degree_distribution_vec.sort_by_key(|a| a.0);
     write(
         "distributions.txt",
         degree_distribution_vec
@@ -153,7 +151,7 @@ pub fn get_cl_ef_dis_par(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>)
         (end - start).as_millis()
     );
 
-    distribution_vec.sort_by(|a, b| a.0.cmp(&b.0));
+    distribution_vec.sort_by_key(|a| a.0);
     write(
         "cls_distribution.txt",
         distribution_vec
@@ -0,0 +1,173 @@
+use std::collections::VecDeque;
+use std::fs::read_to_string;
+
+use rayon_hash::{HashMap, HashSet};
+
+/// Mirrors every `a -> b` entry as `b -> a` so the matrix can be walked as an undirected graph.
+/// `parse_author_file` only stores the `min -> max` direction and the SNAP edge lists list each
+/// edge once, so most of the metrics below need this first.
+pub fn symmetrize(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+) -> HashMap<usize, HashMap<usize, usize>> {
+    let mut result: HashMap<usize, HashMap<usize, usize>> = HashMap::new();
+    for (&from, neighbors) in sparse_matrix.iter() {
+        result.entry(from).or_default();
+        for (&to, &weight) in neighbors.iter() {
+            if from == to {
+                continue;
+            }
+            let forward = result.entry(from).or_default().entry(to).or_insert(0);
+            *forward = (*forward).max(weight);
+            let backward = result.entry(to).or_default().entry(from).or_insert(0);
+            *backward = (*backward).max(weight);
+        }
+    }
+    result
+}
+
+/// Reads a networkx adjacency list (`socfb-Penn94.txt`): every line is a node followed by its
+/// neighbors, `#` starts a comment.
+pub fn read_adjacency_list(path: &str) -> HashMap<usize, HashMap<usize, usize>> {
+    let mut sparse_matrix: HashMap<usize, HashMap<usize, usize>> = HashMap::new();
+    for line in read_to_string(path).unwrap().lines() {
+        let line = line.split('#').next().unwrap();
+        let mut iter = line.split_whitespace();
+        let node = match iter.next() {
+            Some(node) => node.parse::<usize>().unwrap(),
+            None => continue,
+        };
+        let entry = sparse_matrix.entry(node).or_default();
+        for neighbor in iter {
+            entry.insert(neighbor.parse::<usize>().unwrap(), 1);
+        }
+    }
+    sparse_matrix
+}
+
+/// Reads a SNAP edge list (`com-youtube.ungraph.txt`), one `from to` pair per line and `#`
+/// comments. Edges are stored as listed, see `symmetrize`.
+pub fn read_edge_list(path: &str) -> HashMap<usize, HashMap<usize, usize>> {
+    let mut sparse_matrix: HashMap<usize, HashMap<usize, usize>> = HashMap::new();
+    for line in read_to_string(path).unwrap().lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut iter = line.split_whitespace();
+        let (from, to) = match (iter.next(), iter.next()) {
+            (Some(from), Some(to)) => {
+                (from.parse::<usize>().unwrap(), to.parse::<usize>().unwrap())
+            }
+            _ => continue,
+        };
+        sparse_matrix.entry(from).or_default().insert(to, 1);
+    }
+    sparse_matrix
+}
+
+pub fn edge_count(adjacency: &HashMap<usize, HashMap<usize, usize>>) -> usize {
+    adjacency.values().map(|v| v.len()).sum::<usize>() / 2
+}
+
+pub fn bfs_distances(
+    adjacency: &HashMap<usize, HashMap<usize, usize>>,
+    source: usize,
+) -> HashMap<usize, usize> {
+    let mut distances: HashMap<usize, usize> = HashMap::new();
+    let mut queue = VecDeque::new();
+    distances.insert(source, 0);
+    queue.push_back(source);
+
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node];
+        if let Some(neighbors) = adjacency.get(&node) {
+            for &neighbor in neighbors.keys() {
+                if !distances.contains_key(&neighbor) {
+                    distances.insert(neighbor, distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    distances
+}
+
+/// Components are returned largest first, nodes inside a component are sorted.
+pub fn connected_components(adjacency: &HashMap<usize, HashMap<usize, usize>>) -> Vec<Vec<usize>> {
+    let mut nodes: Vec<usize> = adjacency.keys().cloned().collect();
+    nodes.sort_unstable();
+
+    let mut visited: HashSet<usize> = HashSet::new();
+    let mut components = Vec::new();
+    for node in nodes {
+        if visited.contains(&node) {
+            continue;
+        }
+        let mut component: Vec<usize> = bfs_distances(adjacency, node).keys().cloned().collect();
+        component.sort_unstable();
+        visited.extend(component.iter().cloned());
+        components.push(component);
+    }
+
+    components.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+    components
+}
+
+/// Dense-index copy of a sparse matrix for algorithms that sweep over all nodes many times.
+/// Nodes are indexed in ascending id order, neighbor lists are symmetric, sorted by index and
+/// free of self loops, so iteration order never depends on hashing.
+#[derive(Debug, Clone)]
+pub struct IndexedGraph {
+    pub ids: Vec<usize>,
+    pub index: HashMap<usize, usize>,
+    pub neighbors: Vec<Vec<(usize, f64)>>,
+}
+
+impl IndexedGraph {
+    pub fn new(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>) -> Self {
+        let adjacency = symmetrize(sparse_matrix);
+        let mut ids: Vec<usize> = adjacency.keys().cloned().collect();
+        ids.sort_unstable();
+        let index: HashMap<usize, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+        let neighbors = ids
+            .iter()
+            .map(|id| {
+                let mut list: Vec<(usize, f64)> = adjacency[id]
+                    .iter()
+                    .map(|(neighbor, &weight)| (index[neighbor], weight as f64))
+                    .collect();
+                list.sort_by_key(|a| a.0);
+                list
+            })
+            .collect();
+
+        IndexedGraph {
+            ids,
+            index,
+            neighbors,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn degree(&self, node: usize) -> usize {
+        self.neighbors[node].len()
+    }
+
+    /// Sum of the weights of the edges of `node`.
+    pub fn strength(&self, node: usize) -> f64 {
+        self.neighbors[node].iter().map(|&(_, w)| w).sum()
+    }
+
+    /// Sum of all edge weights, every undirected edge counted once.
+    pub fn total_weight(&self) -> f64 {
+        (0..self.len()).map(|node| self.strength(node)).sum::<f64>() / 2.0
+    }
+}
@@ -0,0 +1,282 @@
+use std::fs::read_to_string;
+
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon_hash::{HashMap, HashSet};
+
+use crate::partition::Partition;
+
+/// Possibly overlapping communities, e.g. the SNAP ground truth where a node can belong to many
+/// groups. Members of every community are sorted and unique.
+#[derive(Debug, Clone, Default)]
+pub struct Cover {
+    communities: Vec<Vec<usize>>,
+}
+
+impl Cover {
+    pub fn new(communities: Vec<Vec<usize>>) -> Self {
+        let communities = communities
+            .into_iter()
+            .map(|mut nodes| {
+                nodes.sort_unstable();
+                nodes.dedup();
+                nodes
+            })
+            .filter(|nodes| !nodes.is_empty())
+            .collect();
+        Cover { communities }
+    }
+
+    pub fn from_partition(partition: &Partition) -> Self {
+        Cover::new(partition.communities())
+    }
+
+    /// Reads a SNAP community file (`com-youtube.all.cmty.txt`, `com-youtube.top5000.cmty.txt`),
+    /// one community per line with the member ids separated by whitespace.
+    pub fn read_snap(path: &str) -> Self {
+        let communities = read_to_string(path)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|node| node.parse::<usize>().unwrap())
+                    .collect()
+            })
+            .collect();
+        Cover::new(communities)
+    }
+
+    pub fn communities(&self) -> &[Vec<usize>] {
+        &self.communities
+    }
+
+    pub fn len(&self) -> usize {
+        self.communities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.communities.is_empty()
+    }
+
+    pub fn nodes(&self) -> HashSet<usize> {
+        self.communities.iter().flatten().cloned().collect()
+    }
+
+    /// Indices of the communities of every node.
+    pub fn memberships(&self) -> HashMap<usize, Vec<usize>> {
+        let mut memberships: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (community, nodes) in self.communities.iter().enumerate() {
+            for &node in nodes {
+                memberships.entry(node).or_default().push(community);
+            }
+        }
+        memberships
+    }
+
+    /// Keeps only the members in `nodes`, dropping communities that end up empty. Detected
+    /// communities are usually restricted to the nodes covered by the ground truth first.
+    pub fn restrict(&self, nodes: &HashSet<usize>) -> Cover {
+        Cover::new(
+            self.communities
+                .iter()
+                .map(|community| {
+                    community
+                        .iter()
+                        .cloned()
+                        .filter(|node| nodes.contains(node))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Sizes of the intersections of `community` with the communities of `other` it overlaps.
+fn overlaps(
+    community: &[usize],
+    memberships: &HashMap<usize, Vec<usize>>,
+) -> HashMap<usize, usize> {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for node in community {
+        if let Some(communities) = memberships.get(node) {
+            for &c in communities {
+                *counts.entry(c).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Average over `from` of the best F1 score against any community of `to`.
+fn best_match_f1(from: &Cover, to: &Cover) -> f64 {
+    if from.is_empty() {
+        return 0.0;
+    }
+    let memberships = to.memberships();
+    let total: f64 = from
+        .communities
+        .par_iter()
+        .map(|community| {
+            overlaps(community, &memberships)
+                .into_iter()
+                .map(|(c, common)| {
+                    2.0 * common as f64 / (community.len() + to.communities[c].len()) as f64
+                })
+                .fold(0.0, f64::max)
+        })
+        .sum();
+    total / from.len() as f64
+}
+
+/// Average F1 score (Yang and Leskovec): every community is matched to its best counterpart in
+/// the other cover and the averages of both directions are averaged.
+pub fn f1_score(detected: &Cover, truth: &Cover) -> f64 {
+    (best_match_f1(detected, truth) + best_match_f1(truth, detected)) / 2.0
+}
+
+fn h(p: f64) -> f64 {
+    if p > 0.0 {
+        -p * p.log2()
+    } else {
+        0.0
+    }
+}
+
+/// Conditional entropies `H(X_i | Y)` summed over the communities of `x` as in Lancichinetti et
+/// al., with the constraint on the best match from McDaid et al.
+fn conditional_entropy(x: &Cover, y: &Cover, n: f64) -> f64 {
+    let memberships = y.memberships();
+    x.communities
+        .par_iter()
+        .map(|community| {
+            let common = overlaps(community, &memberships);
+            let size_x = community.len() as f64;
+            let entropy_x = h(size_x / n) + h((n - size_x) / n);
+            let mut best = entropy_x;
+            for (j, other) in y.communities.iter().enumerate() {
+                let both = common.get(&j).cloned().unwrap_or(0) as f64;
+                let size_y = other.len() as f64;
+                let d = both / n;
+                let c = (size_x - both) / n;
+                let b = (size_y - both) / n;
+                let a = 1.0 - b - c - d;
+                if h(a) + h(d) <= h(b) + h(c) {
+                    continue;
+                }
+                let entropy_y = h(size_y / n) + h((n - size_y) / n);
+                best = best.min(h(a) + h(b) + h(c) + h(d) - entropy_y);
+            }
+            best
+        })
+        .sum()
+}
+
+fn cover_entropy(cover: &Cover, n: f64) -> f64 {
+    cover
+        .communities
+        .iter()
+        .map(|community| {
+            let p = community.len() as f64 / n;
+            h(p) + h(1.0 - p)
+        })
+        .sum()
+}
+
+/// Overlapping NMI `I(X; Y) / max(H(X), H(Y))` (McDaid et al.), where every community is a binary
+/// random variable over the nodes of both covers. Compares every pair of communities, so it is
+/// quadratic in the number of communities.
+pub fn overlapping_nmi(x: &Cover, y: &Cover) -> f64 {
+    let n = x.nodes().union(&y.nodes()).count() as f64;
+    if n == 0.0 {
+        return 0.0;
+    }
+    let entropy_x = cover_entropy(x, n);
+    let entropy_y = cover_entropy(y, n);
+    let maximum = entropy_x.max(entropy_y);
+    if maximum == 0.0 {
+        return 1.0;
+    }
+    let mutual =
+        (entropy_x - conditional_entropy(x, y, n) + entropy_y - conditional_entropy(y, x, n)) / 2.0;
+    mutual / maximum
+}
+
+/// Number of communities every co-clustered node pair shares.
+fn pair_counts(cover: &Cover) -> HashMap<(usize, usize), usize> {
+    let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+    for community in cover.communities.iter() {
+        for (i, &a) in community.iter().enumerate() {
+            for &b in &community[i + 1..] {
+                *counts.entry((a, b)).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Omega index (Collins and Dent): share of node pairs that are in the same number of
+/// communities in both covers, adjusted for chance. Enumerates all pairs inside every community,
+/// so very large communities make it slow.
+pub fn omega_index(x: &Cover, y: &Cover) -> f64 {
+    let n = x.nodes().union(&y.nodes()).count() as f64;
+    let pairs = n * (n - 1.0) / 2.0;
+    if pairs == 0.0 {
+        return 1.0;
+    }
+    let counts_x = pair_counts(x);
+    let counts_y = pair_counts(y);
+
+    let mut agree = 0.0;
+    for (pair, &count) in counts_x.iter() {
+        if counts_y.get(pair) == Some(&count) {
+            agree += 1.0;
+        }
+    }
+    let shared: f64 = counts_x
+        .keys()
+        .filter(|pair| counts_y.contains_key(pair))
+        .count() as f64;
+    // pairs together in neither cover
+    let apart = pairs - counts_x.len() as f64 - counts_y.len() as f64 + shared;
+    let observed = (agree + apart) / pairs;
+
+    let histogram = |counts: &HashMap<(usize, usize), usize>| {
+        let mut histogram: HashMap<usize, f64> = HashMap::new();
+        for &count in counts.values() {
+            *histogram.entry(count).or_insert(0.0) += 1.0;
+        }
+        histogram.insert(0, pairs - counts.len() as f64);
+        histogram
+    };
+    let histogram_x = histogram(&counts_x);
+    let histogram_y = histogram(&counts_y);
+    let expected: f64 = histogram_x
+        .iter()
+        .map(|(count, &nx)| nx * histogram_y.get(count).cloned().unwrap_or(0.0))
+        .sum::<f64>()
+        / (pairs * pairs);
+
+    if expected == 1.0 {
+        return 1.0;
+    }
+    (observed - expected) / (1.0 - expected)
+}
+
+pub fn ground_truth_report(name: &str, detected: &Cover, truth: &Cover) {
+    let start = std::time::Instant::now();
+    let detected = detected.restrict(&truth.nodes());
+    let f1 = f1_score(&detected, truth);
+    let nmi = overlapping_nmi(&detected, truth);
+    let omega = omega_index(&detected, truth);
+    let end = std::time::Instant::now();
+    println!(
+        "{} vs ground truth: {} communities against {}, F1 {}, NMI {}, omega {} in {}",
+        name,
+        detected.len(),
+        truth.len(),
+        f1,
+        nmi,
+        omega,
+        (end - start).as_millis()
+    );
+}
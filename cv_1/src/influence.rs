@@ -0,0 +1,611 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rayon_hash::{HashMap, HashSet};
+
+use crate::diffusion::{monte_carlo, DiffusionModel, EdgeProbability};
+use crate::graph::IndexedGraph;
+
+/// Nodes activated by one cascade from `seeds`. Only the touched nodes are stored, so the cost
+/// follows the spread instead of the graph size, which the greedy strategies rely on.
+fn cascade_spread<R: Rng>(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    seeds: &[usize],
+    rng: &mut R,
+) -> usize {
+    let mut active: HashSet<usize> = seeds.iter().cloned().collect();
+    let mut frontier: Vec<usize> = active.iter().cloned().collect();
+    frontier.sort_unstable();
+    let mut thresholds: HashMap<usize, f64> = HashMap::new();
+    let mut influence: HashMap<usize, f64> = HashMap::new();
+
+    while !frontier.is_empty() {
+        let mut next = Vec::new();
+        for &u in frontier.iter() {
+            for &(v, weight) in graph.neighbors[u].iter() {
+                if active.contains(&v) {
+                    continue;
+                }
+                let activated = match model {
+                    DiffusionModel::IndependentCascade(probability) => {
+                        rng.gen::<f64>() < probability.probability(graph, v, weight)
+                    }
+                    DiffusionModel::LinearThreshold => {
+                        let threshold = *thresholds.entry(v).or_insert_with(|| rng.gen::<f64>());
+                        let total = influence.entry(v).or_insert(0.0);
+                        *total += weight / graph.strength(v);
+                        *total >= threshold
+                    }
+                    _ => panic!("cascade_spread needs a cascade model"),
+                };
+                if activated {
+                    active.insert(v);
+                    next.push(v);
+                }
+            }
+        }
+        frontier = next;
+    }
+    active.len()
+}
+
+/// Expected number of activated nodes over `trials` cascades, trial `i` seeded with `seed + i`
+/// so that every seed set is evaluated on the same random numbers. Epidemic models fall back to
+/// the full simulator.
+pub fn estimate_spread(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    seeds: &[usize],
+    trials: usize,
+    seed: u64,
+) -> f64 {
+    match model {
+        DiffusionModel::IndependentCascade(_) | DiffusionModel::LinearThreshold => {
+            let total: usize = (0..trials)
+                .into_par_iter()
+                .map(|i| {
+                    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+                    cascade_spread(graph, model, seeds, &mut rng)
+                })
+                .sum();
+            total as f64 / trials.max(1) as f64
+        }
+        _ => monte_carlo(graph, model, seeds, 500, trials, seed).mean_spread(),
+    }
+}
+
+/// Highest-degree nodes, skipping nodes adjacent to an already selected one, like
+/// `select_initial_nodes` in `cv_7_2.py`.
+pub fn top_degree_independent(graph: &IndexedGraph, k: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..graph.len()).collect();
+    order.sort_by(|&a, &b| graph.degree(b).cmp(&graph.degree(a)).then(a.cmp(&b)));
+    let mut selected: Vec<usize> = Vec::new();
+    for node in order {
+        if selected.len() >= k {
+            break;
+        }
+        let adjacent = selected.iter().any(|&s| {
+            graph.neighbors[s]
+                .binary_search_by_key(&node, |e| e.0)
+                .is_ok()
+        });
+        if !adjacent {
+            selected.push(node);
+        }
+    }
+    selected
+}
+
+pub fn top_degree(graph: &IndexedGraph, k: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..graph.len()).collect();
+    order.sort_by(|&a, &b| graph.degree(b).cmp(&graph.degree(a)).then(a.cmp(&b)));
+    order.truncate(k);
+    order
+}
+
+/// Degree discount heuristic (Chen et al.) for the independent cascade with uniform probability
+/// `p`: a node with `t` selected neighbors is worth `d - 2t - (d - t) t p`.
+pub fn degree_discount(graph: &IndexedGraph, k: usize, p: f64) -> Vec<usize> {
+    let n = graph.len();
+    let degrees: Vec<f64> = (0..n).map(|node| graph.degree(node) as f64).collect();
+    let mut discounted = degrees.clone();
+    let mut selected_neighbors = vec![0.0; n];
+    let mut selected = vec![false; n];
+    let mut result = Vec::with_capacity(k);
+
+    for _ in 0..k.min(n) {
+        let best = (0..n)
+            .filter(|&node| !selected[node])
+            .max_by(|&a, &b| {
+                discounted[a]
+                    .partial_cmp(&discounted[b])
+                    .unwrap_or(Ordering::Equal)
+                    .then(b.cmp(&a))
+            })
+            .unwrap();
+        selected[best] = true;
+        result.push(best);
+        for &(v, _) in graph.neighbors[best].iter() {
+            if selected[v] {
+                continue;
+            }
+            selected_neighbors[v] += 1.0;
+            let t = selected_neighbors[v];
+            discounted[v] = degrees[v] - 2.0 * t - (degrees[v] - t) * t * p;
+        }
+    }
+    result
+}
+
+/// Heap entry of the lazy greedy, largest gain first, ties by the smaller node.
+struct Candidate {
+    gain: f64,
+    node: usize,
+    // |S| when `gain` was computed
+    round: usize,
+    // CELF++: best node of the round when `gain` was computed and the gain given that node
+    previous_best: Option<usize>,
+    gain_with_previous: f64,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.gain
+            .partial_cmp(&other.gain)
+            .unwrap_or(Ordering::Equal)
+            .then(other.node.cmp(&self.node))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GreedyResult {
+    pub seeds: Vec<usize>,
+    pub spread: f64,
+    // Number of spread estimations, the cost the lazy evaluation saves
+    pub evaluations: usize,
+}
+
+/// Greedy hill climbing with CELF lazy evaluation (Leskovec et al.), or CELF++ (Goyal et al.)
+/// when `plus_plus` is set, which also remembers the gain of every node given the current best
+/// candidate and can skip one more re-evaluation after that candidate is picked.
+pub fn celf(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    k: usize,
+    trials: usize,
+    seed: u64,
+    plus_plus: bool,
+) -> GreedyResult {
+    let mut evaluations = 0;
+    let mut spread_of = |seeds: &[usize]| {
+        evaluations += 1;
+        estimate_spread(graph, model, seeds, trials, seed)
+    };
+
+    let mut heap: BinaryHeap<Candidate> = BinaryHeap::new();
+    let mut current_best: Option<(usize, f64)> = None;
+    for node in 0..graph.len() {
+        let gain = spread_of(&[node]);
+        let gain_with_previous = match (plus_plus, current_best) {
+            (true, Some((best, best_spread))) => spread_of(&[node, best]) - best_spread,
+            _ => gain,
+        };
+        heap.push(Candidate {
+            gain,
+            node,
+            round: 0,
+            previous_best: current_best.map(|b| b.0),
+            gain_with_previous,
+        });
+        if current_best.is_none_or(|(_, g)| gain > g) {
+            current_best = Some((node, gain));
+        }
+    }
+
+    let mut seeds: Vec<usize> = Vec::new();
+    let mut spread = 0.0;
+    let mut last_seed: Option<usize> = None;
+    let mut current_best: Option<(usize, f64)> = None;
+    while seeds.len() < k {
+        let mut candidate = match heap.pop() {
+            Some(candidate) => candidate,
+            None => break,
+        };
+        if candidate.round == seeds.len() {
+            seeds.push(candidate.node);
+            spread += candidate.gain;
+            last_seed = Some(candidate.node);
+            current_best = None;
+            continue;
+        }
+
+        if plus_plus
+            && candidate.round + 1 == seeds.len()
+            && candidate.previous_best.is_some()
+            && candidate.previous_best == last_seed
+        {
+            candidate.gain = candidate.gain_with_previous;
+        } else {
+            let mut with = seeds.clone();
+            with.push(candidate.node);
+            candidate.gain = spread_of(&with) - spread;
+            if plus_plus {
+                if let Some((best, best_gain)) = current_best {
+                    with.push(best);
+                    candidate.gain_with_previous = spread_of(&with) - spread - best_gain;
+                }
+                candidate.previous_best = current_best.map(|b| b.0);
+            }
+        }
+        candidate.round = seeds.len();
+        if current_best.is_none_or(|(_, g)| candidate.gain > g) {
+            current_best = Some((candidate.node, candidate.gain));
+        }
+        heap.push(candidate);
+    }
+
+    GreedyResult {
+        seeds,
+        spread,
+        evaluations,
+    }
+}
+
+/// One reverse reachable set: the nodes that would have activated a random root. Live edges are
+/// sampled for the independent cascade, the linear threshold model keeps one random in-neighbor
+/// per node, chosen proportionally to the edge weights.
+fn reverse_reachable<R: Rng>(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    rng: &mut R,
+) -> Vec<usize> {
+    let root = rng.gen_range(0..graph.len());
+    let mut visited: HashSet<usize> = HashSet::new();
+    visited.insert(root);
+    let mut result = vec![root];
+    let mut stack = vec![root];
+    while let Some(v) = stack.pop() {
+        match model {
+            DiffusionModel::IndependentCascade(probability) => {
+                for &(u, weight) in graph.neighbors[v].iter() {
+                    if !visited.contains(&u)
+                        && rng.gen::<f64>() < probability.probability(graph, v, weight)
+                    {
+                        visited.insert(u);
+                        result.push(u);
+                        stack.push(u);
+                    }
+                }
+            }
+            DiffusionModel::LinearThreshold => {
+                let strength = graph.strength(v);
+                if strength == 0.0 {
+                    continue;
+                }
+                let mut target = rng.gen::<f64>() * strength;
+                for &(u, weight) in graph.neighbors[v].iter() {
+                    target -= weight;
+                    if target <= 0.0 {
+                        if visited.insert(u) {
+                            result.push(u);
+                            stack.push(u);
+                        }
+                        break;
+                    }
+                }
+            }
+            _ => panic!("reverse influence sampling needs a cascade model"),
+        }
+    }
+    result
+}
+
+/// Greedy maximum coverage of the reverse reachable sets, returns the seeds and the share of the
+/// sets they cover. Once every set is covered the remaining seeds are the unselected nodes in
+/// index order.
+fn node_selection(sets: &[Vec<usize>], n: usize, k: usize) -> (Vec<usize>, f64) {
+    let mut containing: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (i, set) in sets.iter().enumerate() {
+        for &node in set {
+            containing[node].push(i);
+        }
+    }
+    let mut degrees: Vec<usize> = containing.iter().map(|c| c.len()).collect();
+    let mut covered = vec![false; sets.len()];
+    let mut covered_count = 0;
+    let mut selected = vec![false; n];
+    let mut seeds = Vec::with_capacity(k);
+
+    for _ in 0..k.min(n) {
+        let best = (0..n)
+            .filter(|&v| !selected[v])
+            .max_by(|&a, &b| degrees[a].cmp(&degrees[b]).then(b.cmp(&a)))
+            .unwrap();
+        selected[best] = true;
+        seeds.push(best);
+        if degrees[best] == 0 {
+            continue;
+        }
+        for &i in containing[best].iter() {
+            if covered[i] {
+                continue;
+            }
+            covered[i] = true;
+            covered_count += 1;
+            for &node in sets[i].iter() {
+                degrees[node] -= 1;
+            }
+        }
+    }
+    let share = if sets.is_empty() {
+        0.0
+    } else {
+        covered_count as f64 / sets.len() as f64
+    };
+    (seeds, share)
+}
+
+/// Reverse reachable sets `range` of the stream, set `i` is generated from `seed + i`, so the
+/// sets do not depend on the number of threads.
+fn sample_sets(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    seed: u64,
+    range: Range<usize>,
+) -> Vec<Vec<usize>> {
+    range
+        .into_par_iter()
+        .map(|i| {
+            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+            reverse_reachable(graph, model, &mut rng)
+        })
+        .collect()
+}
+
+fn log_binomial(n: usize, k: usize) -> f64 {
+    (0..k).map(|i| ((n - i) as f64 / (i + 1) as f64).ln()).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RisConfig {
+    pub epsilon: f64,
+    // Success probability 1 - n^-l
+    pub l: f64,
+    pub seed: u64,
+}
+
+impl Default for RisConfig {
+    fn default() -> Self {
+        RisConfig {
+            epsilon: 0.5,
+            l: 1.0,
+            seed: 420,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RisResult {
+    pub seeds: Vec<usize>,
+    // n * covered share, the RIS estimate of the spread
+    pub estimated_spread: f64,
+    pub sets: usize,
+}
+
+/// IMM (Tang et al. 2015): estimates a lower bound of the optimal spread with a doubling search
+/// over the number of reverse reachable sets, then samples enough sets for a
+/// `(1 - 1/e - epsilon)` approximation with probability `1 - n^-l`.
+pub fn imm(graph: &IndexedGraph, model: DiffusionModel, k: usize, config: &RisConfig) -> RisResult {
+    let n = graph.len();
+    if n == 0 || k == 0 {
+        return RisResult {
+            seeds: Vec::new(),
+            estimated_spread: 0.0,
+            sets: 0,
+        };
+    }
+    let nf = n as f64;
+    let k = k.min(n);
+    let l = config.l * (1.0 + 2f64.ln() / nf.ln().max(1.0));
+    let epsilon_prime = 2f64.sqrt() * config.epsilon;
+    let log_choose = log_binomial(n, k);
+
+    let mut sets: Vec<Vec<usize>> = Vec::new();
+    let extend = |sets: &mut Vec<Vec<usize>>, target: usize| {
+        if target > sets.len() {
+            let new = sample_sets(graph, model, config.seed, sets.len()..target);
+            sets.extend(new);
+        }
+    };
+
+    let lambda_prime = (2.0 + 2.0 / 3.0 * epsilon_prime)
+        * (log_choose + l * nf.ln() + nf.log2().max(1.0).ln())
+        * nf
+        / epsilon_prime.powi(2);
+    let mut lower_bound = 1.0;
+    let rounds = (nf.log2() as usize).max(2);
+    for i in 1..rounds {
+        let x = nf / 2f64.powi(i as i32);
+        extend(&mut sets, (lambda_prime / x).ceil() as usize);
+        let (_, share) = node_selection(&sets, n, k);
+        if nf * share >= (1.0 + epsilon_prime) * x {
+            lower_bound = nf * share / (1.0 + epsilon_prime);
+            break;
+        }
+    }
+
+    let e = std::f64::consts::E;
+    let alpha = (l * nf.ln() + 2f64.ln()).sqrt();
+    let beta = ((1.0 - 1.0 / e) * (log_choose + l * nf.ln() + 2f64.ln())).sqrt();
+    let lambda_star = 2.0 * nf * ((1.0 - 1.0 / e) * alpha + beta).powi(2) / config.epsilon.powi(2);
+    extend(&mut sets, (lambda_star / lower_bound).ceil() as usize);
+
+    let (seeds, share) = node_selection(&sets, n, k);
+    RisResult {
+        seeds,
+        estimated_spread: nf * share,
+        sets: sets.len(),
+    }
+}
+
+/// TIM+ (Tang et al. 2014): estimates KPT, the expected spread of `k` nodes picked with
+/// probability proportional to their degree, from the width of the reverse reachable sets,
+/// refines it with a greedy pass over a smaller sample and draws `(8 + 2 epsilon) n (l ln n +
+/// ln C(n, k) + ln 2) / (epsilon^2 KPT+)` sets for the final selection. Uses the same
+/// parameters as `imm`, which needs fewer sets for the same guarantee.
+pub fn tim_plus(
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    k: usize,
+    config: &RisConfig,
+) -> RisResult {
+    let n = graph.len();
+    if n == 0 || k == 0 {
+        return RisResult {
+            seeds: Vec::new(),
+            estimated_spread: 0.0,
+            sets: 0,
+        };
+    }
+    let nf = n as f64;
+    let k = k.min(n);
+    // three sampling phases, each allowed to fail with probability n^-l / 3
+    let l = config.l + 3f64.ln() / nf.ln().max(1.0);
+    let epsilon = config.epsilon;
+    let edges = (0..n).map(|v| graph.degree(v)).sum::<usize>().max(1) as f64;
+
+    let mut next = 0;
+    let mut draw = |count: usize| {
+        let sets = sample_sets(graph, model, config.seed, next..next + count);
+        next += count;
+        sets
+    };
+
+    let mut kpt = 1.0;
+    let rounds = (nf.log2() as usize).max(2);
+    for i in 1..rounds {
+        let count = ((6.0 * l * nf.ln() + 6.0 * nf.log2().max(1.0).ln()) * 2f64.powi(i as i32))
+            .ceil() as usize;
+        let sum: f64 = draw(count)
+            .iter()
+            .map(|set| {
+                let width: usize = set.iter().map(|&v| graph.degree(v)).sum();
+                1.0 - (1.0 - width as f64 / edges).powi(k as i32)
+            })
+            .sum();
+        if sum / count as f64 > 1.0 / 2f64.powi(i as i32) {
+            kpt = nf * sum / (2.0 * count as f64);
+            break;
+        }
+    }
+
+    let epsilon_prime = 5.0 * (l * epsilon.powi(2) / (k as f64 + l)).cbrt();
+    let lambda_prime = (2.0 + epsilon_prime) * l * nf * nf.ln() / epsilon_prime.powi(2);
+    let (_, share) = node_selection(&draw((lambda_prime / kpt).ceil() as usize), n, k);
+    let kpt_plus = (nf * share / (1.0 + epsilon_prime)).max(kpt);
+
+    let log_choose = log_binomial(n, k);
+    let lambda =
+        (8.0 + 2.0 * epsilon) * nf * (l * nf.ln() + log_choose + 2f64.ln()) / epsilon.powi(2);
+    let sets = draw((lambda / kpt_plus).ceil() as usize);
+    let (seeds, share) = node_selection(&sets, n, k);
+    RisResult {
+        seeds,
+        estimated_spread: nf * share,
+        sets: sets.len(),
+    }
+}
+
+/// Picks `k` seeds with every strategy and evaluates them on the same random numbers. Degree
+/// discount assumes a uniform independent cascade and only runs for that model.
+pub fn compare_strategies(
+    name: &str,
+    graph: &IndexedGraph,
+    model: DiffusionModel,
+    k: usize,
+    trials: usize,
+    greedy: bool,
+) {
+    let mut strategies: Vec<(&str, Vec<usize>, u128)> = Vec::new();
+    let mut timed = |label: &'static str, f: &dyn Fn() -> Vec<usize>| {
+        let start = std::time::Instant::now();
+        let seeds = f();
+        let end = std::time::Instant::now();
+        strategies.push((label, seeds, (end - start).as_millis()));
+    };
+
+    timed("random", &|| {
+        let mut rng = StdRng::seed_from_u64(420);
+        sample(&mut rng, graph.len(), k.min(graph.len())).into_vec()
+    });
+    timed("top degree", &|| top_degree(graph, k));
+    timed("top degree independent", &|| {
+        top_degree_independent(graph, k)
+    });
+    if let DiffusionModel::IndependentCascade(EdgeProbability::Uniform(p)) = model {
+        timed("degree discount", &|| degree_discount(graph, k, p));
+    }
+    if greedy {
+        timed("celf", &|| {
+            let result = celf(graph, model, k, trials, 420, false);
+            println!("{} celf: {} evaluations", name, result.evaluations);
+            result.seeds
+        });
+        timed("celf++", &|| {
+            let result = celf(graph, model, k, trials, 420, true);
+            println!("{} celf++: {} evaluations", name, result.evaluations);
+            result.seeds
+        });
+    }
+    timed("imm", &|| {
+        let result = imm(graph, model, k, &RisConfig::default());
+        println!(
+            "{} imm: {} RR sets, estimated spread {}",
+            name, result.sets, result.estimated_spread
+        );
+        result.seeds
+    });
+    timed("tim+", &|| {
+        let result = tim_plus(graph, model, k, &RisConfig::default());
+        println!(
+            "{} tim+: {} RR sets, estimated spread {}",
+            name, result.sets, result.estimated_spread
+        );
+        result.seeds
+    });
+
+    for (label, seeds, millis) in strategies {
+        // evaluated with a different seed than the one the greedy strategies optimized on
+        let spread = estimate_spread(graph, model, &seeds, trials.max(1000), 4200);
+        let ids: Vec<String> = seeds.iter().map(|&s| graph.ids[s].to_string()).collect();
+        println!(
+            "{} {} {}: seeds {}, expected spread {} in {}",
+            name,
+            model.name(),
+            label,
+            ids.join(" "),
+            spread,
+            millis
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rayon_hash::HashMap;
+
+use crate::graph::IndexedGraph;
+use crate::louvain::{renumber, Level};
+use crate::partition::Partition;
+
+// Minimal codelength improvement (bits) for a node to change module
+const MIN_IMPROVEMENT: f64 = 1e-10;
+
+#[derive(Debug, Clone)]
+pub struct InfomapResult {
+    pub partition: Partition,
+    // Two-level map equation codelength in bits
+    pub codelength: f64,
+    // Codelength with every node in one module, for comparison
+    pub one_module_codelength: f64,
+    pub levels: usize,
+}
+
+fn plogp(p: f64) -> f64 {
+    if p > 0.0 {
+        p * p.log2()
+    } else {
+        0.0
+    }
+}
+
+/// Module flows of the two-level map equation for undirected graphs, where the stationary visit
+/// rate of a node is its strength over `2m`. Weights are kept unnormalized and divided by `m2`
+/// only inside the log terms.
+struct MapEquation {
+    m2: f64,
+    // sum over original nodes of plogp(visit rate), constant during optimization
+    node_entropy: f64,
+    volume: Vec<f64>,
+    internal: Vec<f64>,
+    sum_exit: f64,
+    sum_plogp_exit: f64,
+    sum_plogp_total: f64,
+}
+
+impl MapEquation {
+    fn new(level: &Level, m2: f64, node_entropy: f64, modules: &[usize]) -> Self {
+        let n = level.len();
+        let mut volume = vec![0.0; n];
+        let mut internal = vec![0.0; n];
+        for node in 0..n {
+            let module = modules[node];
+            volume[module] += level.degrees[node];
+            internal[module] += level.self_loops[node];
+            for &(neighbor, weight) in level.neighbors[node].iter() {
+                if modules[neighbor] == module {
+                    internal[module] += weight;
+                }
+            }
+        }
+        let mut map = MapEquation {
+            m2,
+            node_entropy,
+            volume,
+            internal,
+            sum_exit: 0.0,
+            sum_plogp_exit: 0.0,
+            sum_plogp_total: 0.0,
+        };
+        for module in 0..n {
+            map.add_terms(module, 1.0);
+        }
+        map
+    }
+
+    fn exit(&self, module: usize) -> f64 {
+        (self.volume[module] - self.internal[module]).max(0.0)
+    }
+
+    fn add_terms(&mut self, module: usize, sign: f64) {
+        let exit = self.exit(module);
+        self.sum_exit += sign * exit;
+        self.sum_plogp_exit += sign * plogp(exit / self.m2);
+        self.sum_plogp_total += sign * plogp((exit + self.volume[module]) / self.m2);
+    }
+
+    fn codelength(&self) -> f64 {
+        plogp(self.sum_exit / self.m2) - 2.0 * self.sum_plogp_exit - self.node_entropy
+            + self.sum_plogp_total
+    }
+
+    /// Adds (`sign = 1`) or removes (`sign = -1`) a node with `degree`, `self_loop` and `links`
+    /// edge weight to the other members of `module`.
+    fn update(&mut self, module: usize, degree: f64, self_loop: f64, links: f64, sign: f64) {
+        self.add_terms(module, -1.0);
+        self.volume[module] += sign * degree;
+        self.internal[module] += sign * (self_loop + 2.0 * links);
+        self.add_terms(module, 1.0);
+    }
+}
+
+/// Moves nodes between modules while the codelength decreases. Returns whether any node moved.
+fn local_moving(
+    level: &Level,
+    modules: &mut [usize],
+    map: &mut MapEquation,
+    rng: &mut StdRng,
+    max_passes: usize,
+) -> bool {
+    let n = level.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(rng);
+    let mut weights_to = vec![0.0; n];
+    let mut touched: Vec<usize> = Vec::new();
+    let mut moved_any = false;
+
+    for _ in 0..max_passes {
+        let mut moved = false;
+        for &node in order.iter() {
+            let current = modules[node];
+            let degree = level.degrees[node];
+            let self_loop = level.self_loops[node];
+
+            for &(neighbor, weight) in level.neighbors[node].iter() {
+                let m = modules[neighbor];
+                if weights_to[m] == 0.0 {
+                    touched.push(m);
+                }
+                weights_to[m] += weight;
+            }
+
+            map.update(current, degree, self_loop, weights_to[current], -1.0);
+            let mut best = current;
+            map.update(current, degree, self_loop, weights_to[current], 1.0);
+            let mut best_length = map.codelength();
+            map.update(current, degree, self_loop, weights_to[current], -1.0);
+
+            for &m in touched.iter() {
+                if m == current {
+                    continue;
+                }
+                map.update(m, degree, self_loop, weights_to[m], 1.0);
+                let length = map.codelength();
+                map.update(m, degree, self_loop, weights_to[m], -1.0);
+                if length < best_length - MIN_IMPROVEMENT {
+                    best = m;
+                    best_length = length;
+                }
+            }
+
+            map.update(best, degree, self_loop, weights_to[best], 1.0);
+            if best != current {
+                modules[node] = best;
+                moved = true;
+                moved_any = true;
+            }
+
+            for &m in touched.iter() {
+                weights_to[m] = 0.0;
+            }
+            touched.clear();
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    moved_any
+}
+
+/// Infomap-style two-level map equation optimization: Louvain-like local moving that minimizes
+/// the codelength instead of maximizing modularity, followed by aggregation of the modules.
+pub fn infomap(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    seed: u64,
+    max_levels: usize,
+) -> InfomapResult {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut level = Level::from_graph(&graph);
+    let m2: f64 = level.degrees.iter().sum();
+
+    if m2 == 0.0 {
+        return InfomapResult {
+            partition: Partition::new(graph.ids.iter().map(|&id| (id, id)).collect()).normalized(),
+            codelength: 0.0,
+            one_module_codelength: 0.0,
+            levels: 0,
+        };
+    }
+
+    let node_entropy: f64 = level.degrees.iter().map(|&d| plogp(d / m2)).sum();
+    let one_module_codelength = -node_entropy;
+
+    let mut node_level: Vec<usize> = (0..graph.len()).collect();
+    let mut modules: Vec<usize> = (0..graph.len()).collect();
+    let mut codelength;
+    let mut levels = 0;
+
+    loop {
+        let mut map = MapEquation::new(&level, m2, node_entropy, &modules);
+        let moved = local_moving(&level, &mut modules, &mut map, &mut rng, 100);
+        codelength = map.codelength();
+        let count = renumber(&mut modules);
+        levels += 1;
+        if !moved || count == level.len() || levels >= max_levels {
+            break;
+        }
+        level = level.aggregate(&modules, count);
+        for node in node_level.iter_mut() {
+            *node = modules[*node];
+        }
+        modules = (0..count).collect();
+    }
+
+    let partition = Partition::new(
+        graph
+            .ids
+            .iter()
+            .zip(node_level.iter())
+            .map(|(&id, &node)| (id, modules[node]))
+            .collect(),
+    )
+    .normalized();
+
+    InfomapResult {
+        partition,
+        codelength,
+        one_module_codelength,
+        levels,
+    }
+}
@@ -0,0 +1,133 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+use crate::graph::IndexedGraph;
+use crate::partition::Partition;
+
+/// Labels with the highest total edge weight among the neighbors of `node`, sorted.
+fn best_labels(graph: &IndexedGraph, labels: &[usize], node: usize) -> Vec<usize> {
+    let mut weights: HashMap<usize, f64> = HashMap::new();
+    for &(neighbor, weight) in graph.neighbors[node].iter() {
+        *weights.entry(labels[neighbor]).or_insert(0.0) += weight;
+    }
+    let max = weights.values().cloned().fold(0.0, f64::max);
+    let mut best: Vec<usize> = weights
+        .into_iter()
+        .filter(|&(_, w)| w == max)
+        .map(|(label, _)| label)
+        .collect();
+    best.sort_unstable();
+    best
+}
+
+fn to_partition(graph: &IndexedGraph, labels: &[usize]) -> Partition {
+    Partition::new(
+        graph
+            .ids
+            .iter()
+            .zip(labels.iter())
+            .map(|(&id, &label)| (id, label))
+            .collect(),
+    )
+    .normalized()
+}
+
+/// Asynchronous label propagation (Raghavan et al.): nodes are updated one at a time in a seeded
+/// random order and ties are broken at random, keeping the current label when it is among the
+/// best. Stops after a sweep without changes or `max_iterations` sweeps.
+pub fn label_propagation_async(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    seed: u64,
+    max_iterations: usize,
+) -> Partition {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut labels: Vec<usize> = (0..graph.len()).collect();
+    let mut order: Vec<usize> = (0..graph.len()).collect();
+
+    for _ in 0..max_iterations {
+        order.shuffle(&mut rng);
+        let mut changed = false;
+        for &node in order.iter() {
+            let best = best_labels(&graph, &labels, node);
+            if best.is_empty() || best.contains(&labels[node]) {
+                continue;
+            }
+            labels[node] = best[rng.gen_range(0..best.len())];
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    to_partition(&graph, &labels)
+}
+
+/// Greedy coloring in node index order, returned as color classes.
+fn color_classes(graph: &IndexedGraph) -> Vec<Vec<usize>> {
+    let mut colors = vec![usize::MAX; graph.len()];
+    let mut classes: Vec<Vec<usize>> = Vec::new();
+    for node in 0..graph.len() {
+        let mut used: Vec<usize> = graph.neighbors[node]
+            .iter()
+            .map(|&(neighbor, _)| colors[neighbor])
+            .filter(|&c| c != usize::MAX)
+            .collect();
+        used.sort_unstable();
+        used.dedup();
+        let color = used
+            .iter()
+            .enumerate()
+            .find(|&(i, &c)| i != c)
+            .map_or(used.len(), |(i, _)| i);
+        colors[node] = color;
+        if color == classes.len() {
+            classes.push(Vec::new());
+        }
+        classes[color].push(node);
+    }
+    classes
+}
+
+/// Semi-synchronous label propagation (Cordasco and Gargano): the graph is colored so that
+/// adjacent nodes never share a color, and all nodes of one color are updated at the same time in
+/// parallel. Ties keep the current label, otherwise the smallest label wins, so the result is
+/// deterministic.
+pub fn label_propagation_semi_sync(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    max_iterations: usize,
+) -> Partition {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let classes = color_classes(&graph);
+    let mut labels: Vec<usize> = (0..graph.len()).collect();
+
+    for _ in 0..max_iterations {
+        let mut changed = false;
+        for class in classes.iter() {
+            let updates: Vec<(usize, usize)> = class
+                .par_iter()
+                .filter_map(|&node| {
+                    let best = best_labels(&graph, &labels, node);
+                    if best.is_empty() || best.contains(&labels[node]) {
+                        None
+                    } else {
+                        Some((node, best[0]))
+                    }
+                })
+                .collect();
+            changed |= !updates.is_empty();
+            for (node, label) in updates {
+                labels[node] = label;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    to_partition(&graph, &labels)
+}
@@ -0,0 +1,206 @@
+use std::cmp::Ordering;
+use std::fs::write;
+
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rayon_hash::HashSet;
+
+use crate::multilayer::MultilayerGraph;
+
+fn jaccard<T: Eq + std::hash::Hash>(a: &HashSet<T>, b: &HashSet<T>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+fn layer_edges(graph: &MultilayerGraph, layer: usize) -> HashSet<(usize, usize)> {
+    graph
+        .layer(layer)
+        .iter()
+        .flat_map(|(&a, neighbors)| neighbors.keys().map(move |&b| (a, b)))
+        .filter(|&(a, b)| a < b)
+        .collect()
+}
+
+fn layer_nodes(graph: &MultilayerGraph, layer: usize) -> HashSet<usize> {
+    graph.layer(layer).keys().cloned().collect()
+}
+
+pub fn edge_jaccard(graph: &MultilayerGraph, layer1: usize, layer2: usize) -> f64 {
+    jaccard(&layer_edges(graph, layer1), &layer_edges(graph, layer2))
+}
+
+pub fn node_overlap(graph: &MultilayerGraph, layer1: usize, layer2: usize) -> f64 {
+    jaccard(&layer_nodes(graph, layer1), &layer_nodes(graph, layer2))
+}
+
+/// Degree of every node of the multiplex in `layer`, 0 where the node has no edge.
+pub fn degree_vector(graph: &MultilayerGraph, layer: usize) -> Vec<f64> {
+    (0..graph.node_count())
+        .map(|node| graph.degree(node, layer) as f64)
+        .collect()
+}
+
+pub fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    if x.is_empty() {
+        return 0.0;
+    }
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let mut cov = 0.0;
+    let mut var_x = 0.0;
+    let mut var_y = 0.0;
+    for (a, b) in x.iter().zip(y.iter()) {
+        cov += (a - mean_x) * (b - mean_y);
+        var_x += (a - mean_x).powi(2);
+        var_y += (b - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return 0.0;
+    }
+    cov / (var_x.sqrt() * var_y.sqrt())
+}
+
+/// Ranks starting at 1, tied values share the average of their ranks.
+pub fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap_or(Ordering::Equal));
+    let mut result = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for &index in &order[i..=j] {
+            result[index] = rank;
+        }
+        i = j + 1;
+    }
+    result
+}
+
+pub fn spearman(x: &[f64], y: &[f64]) -> f64 {
+    pearson(&ranks(x), &ranks(y))
+}
+
+/// Symmetric `layers x layers` matrix of `metric` over every layer pair, computed in parallel.
+pub fn layer_matrix<F>(graph: &MultilayerGraph, metric: F) -> Vec<Vec<f64>>
+where
+    F: Fn(&MultilayerGraph, usize, usize) -> f64 + Sync,
+{
+    let n = graph.layer_count();
+    let pairs: Vec<(usize, usize)> = (0..n).flat_map(|i| (i..n).map(move |j| (i, j))).collect();
+    let values: Vec<(usize, usize, f64)> = pairs
+        .into_par_iter()
+        .map(|(i, j)| (i, j, metric(graph, i, j)))
+        .collect();
+
+    let mut matrix = vec![vec![0.0; n]; n];
+    for (i, j, value) in values {
+        matrix[i][j] = value;
+        matrix[j][i] = value;
+    }
+    matrix
+}
+
+pub fn edge_jaccard_matrix(graph: &MultilayerGraph) -> Vec<Vec<f64>> {
+    layer_matrix(graph, edge_jaccard)
+}
+
+pub fn node_overlap_matrix(graph: &MultilayerGraph) -> Vec<Vec<f64>> {
+    layer_matrix(graph, node_overlap)
+}
+
+pub fn degree_pearson_matrix(graph: &MultilayerGraph) -> Vec<Vec<f64>> {
+    layer_matrix(graph, |g, a, b| {
+        pearson(&degree_vector(g, a), &degree_vector(g, b))
+    })
+}
+
+pub fn degree_spearman_matrix(graph: &MultilayerGraph) -> Vec<Vec<f64>> {
+    layer_matrix(graph, |g, a, b| {
+        spearman(&degree_vector(g, a), &degree_vector(g, b))
+    })
+}
+
+/// Multiplex participation coefficient `L / (L - 1) * (1 - sum_l (k_l / k)^2)` of every node,
+/// 0 for nodes without edges or graphs with a single layer.
+pub fn participation_coefficients(graph: &MultilayerGraph) -> Vec<f64> {
+    let layers = graph.layer_count();
+    (0..graph.node_count())
+        .into_par_iter()
+        .map(|node| {
+            let degrees: Vec<f64> = (0..layers)
+                .map(|layer| graph.degree(node, layer) as f64)
+                .collect();
+            let total: f64 = degrees.iter().sum();
+            if layers < 2 || total == 0.0 {
+                return 0.0;
+            }
+            let concentration: f64 = degrees.iter().map(|k| (k / total).powi(2)).sum();
+            layers as f64 / (layers - 1) as f64 * (1.0 - concentration)
+        })
+        .collect()
+}
+
+pub fn write_layer_matrix(path: &str, graph: &MultilayerGraph, matrix: &[Vec<f64>]) {
+    let names: Vec<&str> = graph
+        .all_layers()
+        .into_iter()
+        .map(|layer| graph.layer_name(layer))
+        .collect();
+    let mut lines = vec![format!("layer,{}", names.join(","))];
+    for (name, row) in names.iter().zip(matrix.iter()) {
+        lines.push(format!(
+            "{},{}",
+            name,
+            row.iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        ));
+    }
+    write(path, lines.join("\n").as_bytes()).unwrap();
+}
+
+pub fn layer_similarity_report(name: &str, graph: &MultilayerGraph) {
+    let start = std::time::Instant::now();
+    write_layer_matrix(
+        &format!("{}_edge_jaccard.csv", name),
+        graph,
+        &edge_jaccard_matrix(graph),
+    );
+    write_layer_matrix(
+        &format!("{}_node_overlap.csv", name),
+        graph,
+        &node_overlap_matrix(graph),
+    );
+    write_layer_matrix(
+        &format!("{}_degree_pearson.csv", name),
+        graph,
+        &degree_pearson_matrix(graph),
+    );
+    write_layer_matrix(
+        &format!("{}_degree_spearman.csv", name),
+        graph,
+        &degree_spearman_matrix(graph),
+    );
+
+    let participation = participation_coefficients(graph);
+    let mut lines = vec![String::from("node,participation")];
+    for (node, value) in participation.iter().enumerate() {
+        lines.push(format!("{},{}", graph.node_label(node), value));
+    }
+    write(
+        format!("{}_participation.csv", name),
+        lines.join("\n").as_bytes(),
+    )
+    .unwrap();
+
+    let end = std::time::Instant::now();
+    println!("Layer similarity {} in {}", name, (end - start).as_millis());
+}
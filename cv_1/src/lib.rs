@@ -0,0 +1,36 @@
+pub mod author_profile;
+pub mod burstiness;
+pub mod centrality;
+pub mod collaboration;
+pub mod cv_1;
+pub mod cv_2;
+pub mod cv_3;
+pub mod cv_4;
+pub mod cv_6;
+pub mod cv_7;
+pub mod diffusion;
+pub mod diffusion_trace;
+pub mod epidemic;
+pub mod evolution;
+pub mod flattening;
+pub mod functions;
+pub mod functions_par;
+pub mod graph;
+pub mod ground_truth;
+pub mod influence;
+pub mod infomap;
+pub mod label_propagation;
+pub mod layer_similarity;
+pub mod link_evaluation;
+pub mod link_prediction;
+pub mod louvain;
+pub mod multilayer;
+pub mod multilayer_centrality;
+pub mod multislice;
+pub mod partition;
+pub mod partition_quality;
+pub mod path_prediction;
+pub mod random_walk;
+pub mod spectral_centrality;
+pub mod temporal;
+pub mod temporal_prediction;
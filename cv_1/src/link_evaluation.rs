@@ -0,0 +1,408 @@
+use std::cmp::Ordering;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon_hash::{HashMap, HashSet};
+
+use crate::burstiness::mean_std;
+use crate::graph::{symmetrize, IndexedGraph};
+use crate::layer_similarity::ranks;
+use crate::link_prediction::{sample_distance_two, score_pairs, LinkPredictor};
+
+// Held out edges, distance-2 non-edges and uniform non-edges sampled per split
+pub(crate) const CANDIDATE_SAMPLES: usize = 10000;
+pub(crate) const SAMPLE_SEED: u64 = 420;
+
+/// Training graph with the held out edges, node id pairs with `a < b`.
+#[derive(Debug, Clone)]
+pub struct Split {
+    pub train: HashMap<usize, HashMap<usize, usize>>,
+    pub test: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LinkMetrics {
+    pub precision_at_k: f64,
+    pub recall_at_k: f64,
+    pub auc_roc: f64,
+    pub auc_pr: f64,
+    pub average_precision: f64,
+}
+
+fn undirected_edges(adjacency: &HashMap<usize, HashMap<usize, usize>>) -> Vec<(usize, usize)> {
+    let mut edges: Vec<(usize, usize)> = adjacency
+        .iter()
+        .flat_map(|(&a, neighbors)| neighbors.keys().map(move |&b| (a, b)))
+        .filter(|&(a, b)| a < b)
+        .collect();
+    edges.sort_unstable();
+    edges
+}
+
+/// Shuffles the edges with `seed` and deals them into `k` folds like `cv_3.py`. Every split
+/// removes one fold from the graph, nodes left without edges stay in the training graph. The
+/// splits are built one at a time as the iterator advances.
+pub fn k_fold_splits(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    k: usize,
+    seed: u64,
+) -> impl Iterator<Item = Split> {
+    let adjacency = symmetrize(sparse_matrix);
+    let mut edges = undirected_edges(&adjacency);
+    edges.shuffle(&mut StdRng::seed_from_u64(seed));
+
+    (0..k).map(move |fold| {
+        let test: Vec<(usize, usize)> = edges.iter().skip(fold).step_by(k).cloned().collect();
+        let mut train = adjacency.clone();
+        for &(a, b) in test.iter() {
+            train.get_mut(&a).unwrap().remove(&b);
+            train.get_mut(&b).unwrap().remove(&a);
+        }
+        Split { train, test }
+    })
+}
+
+/// Trains on the DBLP years up to `train_until` and holds out the pairs that first collaborate
+/// later, as long as both authors are already in the training graph.
+pub fn temporal_split(
+    data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>,
+    train_until: u32,
+) -> Split {
+    temporal_window_split(data, train_until, u32::MAX)
+}
+
+/// Like `temporal_split`, but only the years `train_until + 1 ..= train_until + horizon` are
+/// held out.
+pub fn temporal_window_split(
+    data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>,
+    train_until: u32,
+    horizon: u32,
+) -> Split {
+    let mut train: HashMap<usize, HashMap<usize, usize>> = HashMap::new();
+    let mut later: HashSet<(usize, usize)> = HashSet::new();
+    for (&year, matrix) in data.iter() {
+        if year > train_until.saturating_add(horizon) {
+            continue;
+        }
+        for (&author1, links) in matrix.iter() {
+            for (&author2, &weight) in links.iter() {
+                if year <= train_until {
+                    *train
+                        .entry(author1)
+                        .or_default()
+                        .entry(author2)
+                        .or_insert(0) += weight;
+                } else {
+                    later.insert((author1.min(author2), author1.max(author2)));
+                }
+            }
+        }
+    }
+    let train = symmetrize(&train);
+
+    let mut test: Vec<(usize, usize)> = later
+        .into_iter()
+        .filter(|(a, b)| {
+            train.contains_key(a) && train.contains_key(b) && !train[a].contains_key(b)
+        })
+        .collect();
+    test.sort_unstable();
+    Split { train, test }
+}
+
+/// Mann-Whitney estimate of the probability that a positive outscores a negative, ties count
+/// half.
+fn auc(scores: &[f64], labels: &[bool]) -> f64 {
+    let positives = labels.iter().filter(|&&l| l).count() as f64;
+    let negatives = labels.len() as f64 - positives;
+    let ranks = ranks(scores);
+    let positive_ranks: f64 = (0..scores.len())
+        .filter(|&i| labels[i])
+        .map(|i| ranks[i])
+        .sum();
+    (positive_ranks - positives * (positives + 1.0) / 2.0) / (positives * negatives)
+}
+
+/// Metrics of scored candidates with `labels[i]` telling whether candidate `i` is a held out
+/// edge. `k` is the length of the top list, candidates with equal scores keep their order. AUC-ROC
+/// here is over the given list.
+pub fn evaluate_scores(scores: &[f64], labels: &[bool], k: usize) -> LinkMetrics {
+    let positives = labels.iter().filter(|&&l| l).count() as f64;
+    let negatives = labels.len() as f64 - positives;
+    if positives == 0.0 {
+        return LinkMetrics::default();
+    }
+
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap_or(Ordering::Equal));
+
+    let hits = order.iter().take(k).filter(|&&i| labels[i]).count() as f64;
+    let precision_at_k = if k == 0 { 0.0 } else { hits / k as f64 };
+    let recall_at_k = hits / positives;
+
+    let auc_roc = if negatives == 0.0 {
+        1.0
+    } else {
+        auc(scores, labels)
+    };
+
+    // Precision-recall curve with one point per distinct score
+    let mut true_positives = 0.0;
+    let mut seen = 0.0;
+    let mut average_precision = 0.0;
+    let mut auc_pr = 0.0;
+    let (mut last_recall, mut last_precision) = (0.0, 1.0);
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && scores[order[j + 1]] == scores[order[i]] {
+            j += 1;
+        }
+        let group_hits = order[i..=j].iter().filter(|&&x| labels[x]).count() as f64;
+        true_positives += group_hits;
+        seen += (j - i + 1) as f64;
+        let recall = true_positives / positives;
+        let precision = true_positives / seen;
+        average_precision += (recall - last_recall) * precision;
+        auc_pr += (recall - last_recall) * (precision + last_precision) / 2.0;
+        last_recall = recall;
+        last_precision = precision;
+        i = j + 1;
+    }
+
+    LinkMetrics {
+        precision_at_k,
+        recall_at_k,
+        auc_roc,
+        auc_pr,
+        average_precision,
+    }
+}
+
+/// Uniformly random unconnected index pairs that are not held out, duplicates allowed.
+fn sample_non_edges(
+    graph: &IndexedGraph,
+    positives: &HashSet<(usize, usize)>,
+    count: usize,
+    seed: u64,
+) -> Vec<(usize, usize)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pairs = Vec::with_capacity(count);
+    let mut attempts = 0;
+    while pairs.len() < count && attempts < 100 * count && graph.len() > 1 {
+        attempts += 1;
+        let a = rng.gen_range(0..graph.len());
+        let b = rng.gen_range(0..graph.len());
+        let pair = (a.min(b), a.max(b));
+        if a == b
+            || positives.contains(&pair)
+            || graph.neighbors[a].binary_search_by_key(&b, |e| e.0).is_ok()
+        {
+            continue;
+        }
+        pairs.push(pair);
+    }
+    pairs
+}
+
+/// Index pairs of the training graph to score for one split. `pairs[..ranked]` are the sampled
+/// held out edges and distance-2 non-edges in pair order, labeled by `labels`, the rest are the
+/// uniformly sampled non-edges for AUC-ROC.
+#[derive(Debug, Clone)]
+pub struct SplitCandidates {
+    pub graph: IndexedGraph,
+    pub pairs: Vec<(usize, usize)>,
+    pub labels: Vec<bool>,
+    pub ranked: usize,
+}
+
+impl SplitCandidates {
+    /// At most `samples` held out edges, `samples` distance-2 non-edges and `samples` uniform
+    /// non-edges, drawn with `seed`. None of the non-edges is a held out edge.
+    pub fn new(split: &Split, samples: usize, seed: u64) -> Self {
+        let graph = IndexedGraph::new(&split.train);
+        let positives: HashSet<(usize, usize)> = split
+            .test
+            .iter()
+            .filter_map(|(a, b)| match (graph.index.get(a), graph.index.get(b)) {
+                (Some(&x), Some(&y)) => Some((x.min(y), x.max(y))),
+                _ => None,
+            })
+            .collect();
+
+        let mut held_out: Vec<(usize, usize)> = positives.iter().cloned().collect();
+        held_out.sort_unstable();
+        held_out.shuffle(&mut StdRng::seed_from_u64(seed));
+        held_out.truncate(samples);
+
+        // sorted by pair, so tied scores do not favor the held out edges
+        let mut labeled: Vec<((usize, usize), bool)> =
+            held_out.into_iter().map(|pair| (pair, true)).collect();
+        labeled.extend(
+            sample_distance_two(&graph, samples, seed)
+                .into_iter()
+                .filter(|pair| !positives.contains(pair))
+                .map(|pair| (pair, false)),
+        );
+        labeled.sort_unstable();
+        let (mut pairs, labels): (Vec<(usize, usize)>, Vec<bool>) = labeled.into_iter().unzip();
+        let ranked = pairs.len();
+        pairs.extend(sample_non_edges(&graph, &positives, samples, seed));
+        SplitCandidates {
+            graph,
+            pairs,
+            labels,
+            ranked,
+        }
+    }
+
+    pub fn positives(&self) -> usize {
+        self.labels.iter().filter(|&&l| l).count()
+    }
+}
+
+/// Scores the candidates using `scorer` (index pairs of the training graph in, one score per
+/// pair out) and evaluates the ranking. `k = None` uses the number of sampled held out edges,
+/// like `cv_3.py`. Precision, recall and the precision-recall metrics rank the held out edges
+/// among the sampled distance-2 non-edges, so they depend on the sample size and only compare
+/// scorers on the same candidates. AUC-ROC compares the held out edges with uniformly sampled
+/// non-edges instead (Lu and Zhou), otherwise held out edges far from each other would only
+/// compete with distance-2 negatives.
+pub fn evaluate_candidates_by<F>(
+    candidates: &SplitCandidates,
+    k: Option<usize>,
+    scorer: F,
+) -> LinkMetrics
+where
+    F: Fn(&IndexedGraph, &[(usize, usize)]) -> Vec<f64>,
+{
+    let ranked = candidates.ranked;
+    let positives = candidates.positives();
+    let scores = scorer(&candidates.graph, &candidates.pairs);
+    let mut metrics = evaluate_scores(
+        &scores[..ranked],
+        &candidates.labels,
+        k.unwrap_or(positives),
+    );
+
+    if positives > 0 && scores.len() > ranked {
+        let mut auc_scores: Vec<f64> = (0..ranked)
+            .filter(|&i| candidates.labels[i])
+            .map(|i| scores[i])
+            .collect();
+        auc_scores.extend_from_slice(&scores[ranked..]);
+        let mut auc_labels = vec![true; positives];
+        auc_labels.resize(auc_scores.len(), false);
+        metrics.auc_roc = auc(&auc_scores, &auc_labels);
+    }
+    metrics
+}
+
+/// `evaluate_candidates_by` on the default sample of the split.
+pub fn evaluate_split_by<F>(split: &Split, k: Option<usize>, scorer: F) -> LinkMetrics
+where
+    F: Fn(&IndexedGraph, &[(usize, usize)]) -> Vec<f64>,
+{
+    let candidates = SplitCandidates::new(split, CANDIDATE_SAMPLES, SAMPLE_SEED);
+    evaluate_candidates_by(&candidates, k, scorer)
+}
+
+pub fn evaluate_split(split: &Split, predictor: LinkPredictor, k: Option<usize>) -> LinkMetrics {
+    evaluate_split_by(split, k, |graph, pairs| {
+        score_pairs(graph, predictor, pairs)
+    })
+}
+
+/// Mean and standard deviation of every metric over the folds.
+pub fn summarize(metrics: &[LinkMetrics]) -> (LinkMetrics, LinkMetrics) {
+    let field = |f: fn(&LinkMetrics) -> f64| mean_std(&metrics.iter().map(f).collect::<Vec<f64>>());
+    let precision = field(|m| m.precision_at_k);
+    let recall = field(|m| m.recall_at_k);
+    let roc = field(|m| m.auc_roc);
+    let pr = field(|m| m.auc_pr);
+    let ap = field(|m| m.average_precision);
+    (
+        LinkMetrics {
+            precision_at_k: precision.0,
+            recall_at_k: recall.0,
+            auc_roc: roc.0,
+            auc_pr: pr.0,
+            average_precision: ap.0,
+        },
+        LinkMetrics {
+            precision_at_k: precision.1,
+            recall_at_k: recall.1,
+            auc_roc: roc.1,
+            auc_pr: pr.1,
+            average_precision: ap.1,
+        },
+    )
+}
+
+pub fn print_summary(name: &str, metrics: &[LinkMetrics]) {
+    let (mean, std) = summarize(metrics);
+    println!(
+        "{}: precision@k {:.4} +- {:.4}, recall@k {:.4} +- {:.4}, AUC-ROC {:.4} +- {:.4}, AUC-PR {:.4} +- {:.4}, AP {:.4} +- {:.4}",
+        name,
+        mean.precision_at_k,
+        std.precision_at_k,
+        mean.recall_at_k,
+        std.recall_at_k,
+        mean.auc_roc,
+        std.auc_roc,
+        mean.auc_pr,
+        std.auc_pr,
+        mean.average_precision,
+        std.average_precision
+    );
+}
+
+/// Every fold is sampled once and scored by all predictors before the next one is built.
+pub fn cross_validation_report(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    k: usize,
+    seed: u64,
+) {
+    let start = std::time::Instant::now();
+    let mut metrics: Vec<Vec<LinkMetrics>> = vec![Vec::new(); LinkPredictor::ALL.len()];
+    let folds = k_fold_splits(sparse_matrix, k, seed)
+        .map(|split| SplitCandidates::new(&split, CANDIDATE_SAMPLES, SAMPLE_SEED));
+    for candidates in folds {
+        for (i, predictor) in LinkPredictor::ALL.iter().enumerate() {
+            metrics[i].push(evaluate_candidates_by(&candidates, None, |graph, pairs| {
+                score_pairs(graph, *predictor, pairs)
+            }));
+        }
+    }
+    for (predictor, metrics) in LinkPredictor::ALL.iter().zip(metrics) {
+        print_summary(&format!("{}-fold {}", k, predictor.name()), &metrics);
+    }
+    let end = std::time::Instant::now();
+    println!("Cross validation in {}", (end - start).as_millis());
+}
+
+pub fn temporal_holdout_report(
+    data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>,
+    train_until: u32,
+) {
+    let start = std::time::Instant::now();
+    let split = temporal_split(data, train_until);
+    println!(
+        "Temporal holdout after {}: {} new pairs",
+        train_until,
+        split.test.len()
+    );
+    let candidates = SplitCandidates::new(&split, CANDIDATE_SAMPLES, SAMPLE_SEED);
+    for predictor in LinkPredictor::ALL {
+        let metrics = evaluate_candidates_by(&candidates, None, |graph, pairs| {
+            score_pairs(graph, predictor, pairs)
+        });
+        print_summary(
+            &format!("Temporal {}", predictor.name()),
+            std::slice::from_ref(&metrics),
+        );
+    }
+    let end = std::time::Instant::now();
+    println!("Temporal holdout in {}", (end - start).as_millis());
+}
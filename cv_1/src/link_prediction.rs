@@ -0,0 +1,297 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+use crate::graph::IndexedGraph;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkPredictor {
+    CommonNeighbors,
+    Jaccard,
+    AdamicAdar,
+    ResourceAllocation,
+    PreferentialAttachment,
+    Salton,
+    Sorensen,
+}
+
+impl LinkPredictor {
+    pub const ALL: [LinkPredictor; 7] = [
+        LinkPredictor::CommonNeighbors,
+        LinkPredictor::Jaccard,
+        LinkPredictor::AdamicAdar,
+        LinkPredictor::ResourceAllocation,
+        LinkPredictor::PreferentialAttachment,
+        LinkPredictor::Salton,
+        LinkPredictor::Sorensen,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LinkPredictor::CommonNeighbors => "common neighbors",
+            LinkPredictor::Jaccard => "jaccard",
+            LinkPredictor::AdamicAdar => "adamic adar",
+            LinkPredictor::ResourceAllocation => "resource allocation",
+            LinkPredictor::PreferentialAttachment => "preferential attachment",
+            LinkPredictor::Salton => "salton",
+            LinkPredictor::Sorensen => "sorensen",
+        }
+    }
+}
+
+/// Common neighbors of two nodes of `graph`, merged from the sorted neighbor lists.
+pub fn common_neighbors(graph: &IndexedGraph, a: usize, b: usize) -> Vec<usize> {
+    let x = &graph.neighbors[a];
+    let y = &graph.neighbors[b];
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < x.len() && j < y.len() {
+        match x[i].0.cmp(&y[j].0) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                result.push(x[i].0);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    result
+}
+
+/// Score of the pair of node indices `a`, `b`. Edge weights are ignored.
+pub fn score(graph: &IndexedGraph, predictor: LinkPredictor, a: usize, b: usize) -> f64 {
+    let ka = graph.degree(a) as f64;
+    let kb = graph.degree(b) as f64;
+    if predictor == LinkPredictor::PreferentialAttachment {
+        return ka * kb;
+    }
+    let common = common_neighbors(graph, a, b);
+    let cn = common.len() as f64;
+    match predictor {
+        LinkPredictor::CommonNeighbors => cn,
+        LinkPredictor::Jaccard => {
+            let union = ka + kb - cn;
+            if union == 0.0 {
+                0.0
+            } else {
+                cn / union
+            }
+        }
+        LinkPredictor::AdamicAdar => common
+            .iter()
+            .map(|&z| (graph.degree(z) as f64).ln())
+            .filter(|&l| l > 0.0)
+            .map(|l| 1.0 / l)
+            .sum(),
+        LinkPredictor::ResourceAllocation => {
+            common.iter().map(|&z| 1.0 / graph.degree(z) as f64).sum()
+        }
+        LinkPredictor::Salton => {
+            if ka * kb == 0.0 {
+                0.0
+            } else {
+                cn / (ka * kb).sqrt()
+            }
+        }
+        LinkPredictor::Sorensen => {
+            if ka + kb == 0.0 {
+                0.0
+            } else {
+                2.0 * cn / (ka + kb)
+            }
+        }
+        LinkPredictor::PreferentialAttachment => unreachable!(),
+    }
+}
+
+/// Unconnected nodes `b > a` with at least one common neighbor with `a`, sorted.
+fn distance_two_of(graph: &IndexedGraph, a: usize) -> Vec<usize> {
+    let mut candidates: Vec<usize> = graph.neighbors[a]
+        .iter()
+        .flat_map(|&(z, _)| graph.neighbors[z].iter().map(|&(b, _)| b))
+        .filter(|&b| b > a)
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates.retain(|b| graph.neighbors[a].binary_search_by_key(b, |e| e.0).is_err());
+    candidates
+}
+
+/// Unconnected index pairs `(a, b)`, `a < b`, with at least one common neighbor, sorted.
+pub fn distance_two_pairs(graph: &IndexedGraph) -> Vec<(usize, usize)> {
+    (0..graph.len())
+        .into_par_iter()
+        .flat_map_iter(|a| distance_two_of(graph, a).into_iter().map(move |b| (a, b)))
+        .collect()
+}
+
+/// Scores index pairs in parallel, in the order given.
+pub fn score_pairs(
+    graph: &IndexedGraph,
+    predictor: LinkPredictor,
+    pairs: &[(usize, usize)],
+) -> Vec<f64> {
+    pairs
+        .par_iter()
+        .map(|&(a, b)| score(graph, predictor, a, b))
+        .collect()
+}
+
+/// Sorts (a, b, score) by descending score, ties by the pair.
+pub fn rank_predictions(predictions: &mut [(usize, usize, f64)]) {
+    predictions.sort_by(|x, y| {
+        y.2.partial_cmp(&x.2)
+            .unwrap_or(Ordering::Equal)
+            .then((x.0, x.1).cmp(&(y.0, y.1)))
+    });
+}
+
+/// Scores the given node id pairs, pairs with a node missing from the graph score 0.
+pub fn score_candidates(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    predictor: LinkPredictor,
+    candidates: &[(usize, usize)],
+) -> Vec<(usize, usize, f64)> {
+    let graph = IndexedGraph::new(sparse_matrix);
+    candidates
+        .par_iter()
+        .map(|&(a, b)| match (graph.index.get(&a), graph.index.get(&b)) {
+            (Some(&x), Some(&y)) => (a, b, score(&graph, predictor, x, y)),
+            _ => (a, b, 0.0),
+        })
+        .collect()
+}
+
+/// Heap entry ordered like `rank_predictions`, so the greatest entry is the worst prediction.
+#[derive(Debug, Clone, Copy)]
+struct Ranked {
+    score: f64,
+    pair: (usize, usize),
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .partial_cmp(&self.score)
+            .unwrap_or(Ordering::Equal)
+            .then(self.pair.cmp(&other.pair))
+    }
+}
+
+/// Adds `entry` and drops the worst one when there are more than `k`.
+fn push_bounded(heap: &mut BinaryHeap<Ranked>, entry: Ranked, k: usize) {
+    heap.push(entry);
+    if heap.len() > k {
+        heap.pop();
+    }
+}
+
+/// The `k` best distance-2 pairs of the graph as node ids with `a < b`, best first, and the
+/// number of pairs scored. Every thread only keeps its own `k` best pairs.
+pub fn predict_links(
+    graph: &IndexedGraph,
+    predictor: LinkPredictor,
+    k: usize,
+) -> (Vec<(usize, usize, f64)>, usize) {
+    let (heap, count) = (0..graph.len())
+        .into_par_iter()
+        .fold(
+            || (BinaryHeap::with_capacity(k + 1), 0),
+            |(mut heap, mut count), a| {
+                for b in distance_two_of(graph, a) {
+                    let entry = Ranked {
+                        score: score(graph, predictor, a, b),
+                        pair: (a, b),
+                    };
+                    push_bounded(&mut heap, entry, k);
+                    count += 1;
+                }
+                (heap, count)
+            },
+        )
+        .reduce(
+            || (BinaryHeap::new(), 0),
+            |(mut heap, count), (other, other_count)| {
+                for entry in other {
+                    push_bounded(&mut heap, entry, k);
+                }
+                (heap, count + other_count)
+            },
+        );
+    // index order is node id order, so the ties stay ordered by node ids
+    let predictions = heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|r| (graph.ids[r.pair.0], graph.ids[r.pair.1], r.score))
+        .collect();
+    (predictions, count)
+}
+
+/// `count` distance-2 index pairs drawn uniformly without replacement, sorted. Every pair gets a
+/// random key from a generator seeded per node and the pairs with the `count` largest keys are
+/// kept, so the sample does not depend on the number of threads.
+pub fn sample_distance_two(graph: &IndexedGraph, count: usize, seed: u64) -> Vec<(usize, usize)> {
+    let heap = (0..graph.len())
+        .into_par_iter()
+        .fold(
+            || BinaryHeap::with_capacity(count + 1),
+            |mut heap, a| {
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(a as u64));
+                for b in distance_two_of(graph, a) {
+                    let entry = Ranked {
+                        score: rng.gen(),
+                        pair: (a, b),
+                    };
+                    push_bounded(&mut heap, entry, count);
+                }
+                heap
+            },
+        )
+        .reduce(BinaryHeap::new, |mut heap, other| {
+            for entry in other {
+                push_bounded(&mut heap, entry, count);
+            }
+            heap
+        });
+    let mut pairs: Vec<(usize, usize)> = heap.into_iter().map(|r| r.pair).collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+pub fn link_prediction_report(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>, k: usize) {
+    let graph = IndexedGraph::new(sparse_matrix);
+    for predictor in LinkPredictor::ALL {
+        let start = std::time::Instant::now();
+        let (predictions, count) = predict_links(&graph, predictor, k);
+        let end = std::time::Instant::now();
+        println!(
+            "Link prediction {}: {} candidates in {}",
+            predictor.name(),
+            count,
+            (end - start).as_millis()
+        );
+        for (a, b, s) in predictions.iter() {
+            println!("{} {} {}", a, b, s);
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+use crate::graph::IndexedGraph;
+use crate::partition::Partition;
+use crate::partition_quality::indexed_modularity;
+
+// Minimal modularity gain for a node to leave its community, avoids flipping on rounding noise.
+const MIN_GAIN: f64 = 1e-12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LouvainConfig {
+    pub resolution: f64,
+    pub seed: u64,
+    pub max_levels: usize,
+    // Maximum number of sweeps over the nodes during one local moving phase
+    pub max_passes: usize,
+}
+
+impl Default for LouvainConfig {
+    fn default() -> Self {
+        LouvainConfig {
+            resolution: 1.0,
+            seed: 420,
+            max_levels: 100,
+            max_passes: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LouvainResult {
+    pub partition: Partition,
+    pub modularity: f64,
+    pub levels: usize,
+}
+
+// (links to other aggregated nodes, self loop weight, degree)
+type AggregatedNode = (Vec<(usize, f64)>, f64, f64);
+
+/// One level of the aggregation hierarchy. `self_loops[c]` is the total weight inside the
+/// aggregated node counted from both ends, so `degrees` already includes it.
+pub(crate) struct Level {
+    pub(crate) neighbors: Vec<Vec<(usize, f64)>>,
+    pub(crate) self_loops: Vec<f64>,
+    pub(crate) degrees: Vec<f64>,
+}
+
+impl Level {
+    pub(crate) fn from_graph(graph: &IndexedGraph) -> Self {
+        let degrees = (0..graph.len()).map(|node| graph.strength(node)).collect();
+        Level {
+            neighbors: graph.neighbors.clone(),
+            self_loops: vec![0.0; graph.len()],
+            degrees,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    /// Collapses every group of `membership` (ids `0..count`) into a single node.
+    pub(crate) fn aggregate(&self, membership: &[usize], count: usize) -> Level {
+        let mut members = vec![Vec::new(); count];
+        for (node, &group) in membership.iter().enumerate() {
+            members[group].push(node);
+        }
+
+        let rows: Vec<AggregatedNode> = members
+            .par_iter()
+            .map(|nodes| {
+                let mut links: HashMap<usize, f64> = HashMap::new();
+                let mut self_loop = 0.0;
+                let mut degree = 0.0;
+                for &node in nodes {
+                    self_loop += self.self_loops[node];
+                    degree += self.degrees[node];
+                    let group = membership[node];
+                    for &(neighbor, weight) in self.neighbors[node].iter() {
+                        if membership[neighbor] == group {
+                            self_loop += weight;
+                        } else {
+                            *links.entry(membership[neighbor]).or_insert(0.0) += weight;
+                        }
+                    }
+                }
+                let mut links: Vec<(usize, f64)> = links.into_iter().collect();
+                links.sort_by_key(|a| a.0);
+                (links, self_loop, degree)
+            })
+            .collect();
+
+        let mut level = Level {
+            neighbors: Vec::with_capacity(count),
+            self_loops: Vec::with_capacity(count),
+            degrees: Vec::with_capacity(count),
+        };
+        for (links, self_loop, degree) in rows {
+            level.neighbors.push(links);
+            level.self_loops.push(self_loop);
+            level.degrees.push(degree);
+        }
+        level
+    }
+}
+
+/// Renumbers group ids to `0..count` in order of first appearance, returns `count`.
+pub(crate) fn renumber(membership: &mut [usize]) -> usize {
+    let mut mapping: HashMap<usize, usize> = HashMap::new();
+    for group in membership.iter_mut() {
+        let next = mapping.len();
+        *group = *mapping.entry(*group).or_insert(next);
+    }
+    mapping.len()
+}
+
+/// Moves single nodes to the neighboring community with the best modularity gain until a sweep
+/// makes no move. Returns whether any node moved.
+fn local_moving(
+    level: &Level,
+    community: &mut [usize],
+    config: &LouvainConfig,
+    rng: &mut StdRng,
+) -> bool {
+    let n = level.len();
+    let m2: f64 = level.degrees.iter().sum();
+    if m2 == 0.0 {
+        return false;
+    }
+
+    let mut totals = vec![0.0; n];
+    for node in 0..n {
+        totals[community[node]] += level.degrees[node];
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(rng);
+
+    let mut weights_to = vec![0.0; n];
+    let mut touched: Vec<usize> = Vec::new();
+    let mut moved_any = false;
+
+    for _ in 0..config.max_passes {
+        let mut moved = false;
+        for &node in order.iter() {
+            let current = community[node];
+            let degree = level.degrees[node];
+            totals[current] -= degree;
+
+            for &(neighbor, weight) in level.neighbors[node].iter() {
+                let c = community[neighbor];
+                if weights_to[c] == 0.0 {
+                    touched.push(c);
+                }
+                weights_to[c] += weight;
+            }
+
+            let gain = |c: usize, w: f64| w - config.resolution * totals[c] * degree / m2;
+            let mut best = current;
+            let mut best_gain = gain(current, weights_to[current]);
+            for &c in touched.iter() {
+                let g = gain(c, weights_to[c]);
+                if g > best_gain + MIN_GAIN {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+
+            for &c in touched.iter() {
+                weights_to[c] = 0.0;
+            }
+            touched.clear();
+
+            totals[best] += degree;
+            if best != current {
+                community[node] = best;
+                moved = true;
+                moved_any = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    moved_any
+}
+
+/// Leiden refinement: starting from singletons, a node that is still alone may join a
+/// sub-community inside its own community when that increases modularity. Sub-communities only
+/// grow along edges, so they are always connected.
+fn refine(
+    level: &Level,
+    community: &[usize],
+    config: &LouvainConfig,
+    rng: &mut StdRng,
+) -> Vec<usize> {
+    let n = level.len();
+    let m2: f64 = level.degrees.iter().sum();
+    let mut refined: Vec<usize> = (0..n).collect();
+    let mut sizes = vec![1usize; n];
+    let mut totals = level.degrees.clone();
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(rng);
+
+    let mut weights_to = vec![0.0; n];
+    let mut touched: Vec<usize> = Vec::new();
+    for &node in order.iter() {
+        if sizes[refined[node]] > 1 || m2 == 0.0 {
+            continue;
+        }
+        let degree = level.degrees[node];
+        for &(neighbor, weight) in level.neighbors[node].iter() {
+            if community[neighbor] != community[node] {
+                continue;
+            }
+            let r = refined[neighbor];
+            if weights_to[r] == 0.0 {
+                touched.push(r);
+            }
+            weights_to[r] += weight;
+        }
+
+        let own = refined[node];
+        let mut best = own;
+        let mut best_gain = 0.0;
+        for &r in touched.iter() {
+            if r == own {
+                continue;
+            }
+            let g = weights_to[r] - config.resolution * totals[r] * degree / m2;
+            if g > best_gain + MIN_GAIN {
+                best = r;
+                best_gain = g;
+            }
+        }
+        for &r in touched.iter() {
+            weights_to[r] = 0.0;
+        }
+        touched.clear();
+
+        if best != own {
+            sizes[own] -= 1;
+            totals[own] -= degree;
+            sizes[best] += 1;
+            totals[best] += degree;
+            refined[node] = best;
+        }
+    }
+
+    refined
+}
+
+fn run(graph: &IndexedGraph, config: &LouvainConfig, leiden: bool) -> LouvainResult {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut level = Level::from_graph(graph);
+    // original node index -> node of the current level
+    let mut node_level: Vec<usize> = (0..graph.len()).collect();
+    let mut community: Vec<usize> = (0..graph.len()).collect();
+    let mut levels = 0;
+
+    while levels < config.max_levels && level.len() > 0 {
+        let moved = local_moving(&level, &mut community, config, &mut rng);
+        let count = renumber(&mut community);
+        levels += 1;
+
+        // Nodes of the next level: refined sub-communities for Leiden, communities for Louvain
+        let (groups, group_count, next_community) = if leiden {
+            let mut groups = refine(&level, &community, config, &mut rng);
+            let group_count = renumber(&mut groups);
+            let mut next = vec![0; group_count];
+            for node in 0..level.len() {
+                next[groups[node]] = community[node];
+            }
+            (groups, group_count, next)
+        } else {
+            (community.clone(), count, (0..count).collect())
+        };
+
+        if (!moved && group_count == count) || group_count == level.len() {
+            break;
+        }
+
+        level = level.aggregate(&groups, group_count);
+        for node in node_level.iter_mut() {
+            *node = groups[*node];
+        }
+        community = next_community;
+    }
+
+    let membership: Vec<usize> = node_level.iter().map(|&node| community[node]).collect();
+    let modularity = indexed_modularity(graph, &membership, config.resolution);
+    let partition = Partition::new(
+        graph
+            .ids
+            .iter()
+            .zip(membership.iter())
+            .map(|(&id, &c)| (id, c))
+            .collect(),
+    )
+    .normalized();
+
+    LouvainResult {
+        partition,
+        modularity,
+        levels,
+    }
+}
+
+pub fn louvain(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    config: &LouvainConfig,
+) -> LouvainResult {
+    run(&IndexedGraph::new(sparse_matrix), config, false)
+}
+
+/// Louvain with the Leiden refinement step between local moving and aggregation, which keeps
+/// every community connected.
+pub fn leiden(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    config: &LouvainConfig,
+) -> LouvainResult {
+    run(&IndexedGraph::new(sparse_matrix), config, true)
+}
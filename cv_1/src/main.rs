@@ -1,11 +1,13 @@
-mod cv_1;
-mod cv_2;
-mod functions;
-mod functions_par;
-
 fn main() {
-    //cv_1::cv_1();
-    cv_2::cv_2();
+    match std::env::args().nth(1).as_deref() {
+        Some("cv_1") => cv_1::cv_1::cv_1(),
+        Some("cv_3") => cv_1::cv_3::cv_3(),
+        Some("cv_4") => cv_1::cv_4::cv_4(),
+        Some("cv_6") => cv_1::cv_6::cv_6(),
+        Some("cv_7") => cv_1::cv_7::cv_7(),
+        Some("sweep") => cv_1::epidemic::sweep(),
+        _ => cv_1::cv_2::cv_2(),
+    }
 }
 
 /*
@@ -0,0 +1,171 @@
+use std::fs::read_to_string;
+
+use rayon_hash::HashMap;
+
+/// Maps external labels to dense ids in order of first appearance.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    labels: Vec<String>,
+    ids: HashMap<String, usize>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner {
+            labels: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    pub fn intern(&mut self, label: &str) -> usize {
+        if let Some(&id) = self.ids.get(label) {
+            return id;
+        }
+        let id = self.labels.len();
+        self.labels.push(label.to_string());
+        self.ids.insert(label.to_string(), id);
+        id
+    }
+
+    pub fn id(&self, label: &str) -> Option<usize> {
+        self.ids.get(label).cloned()
+    }
+
+    pub fn label(&self, id: usize) -> &str {
+        &self.labels[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+/// Undirected multiplex network: every layer is a sparse matrix in the same format as
+/// `cv_1::cv_1`, indexed by node ids shared across layers. Edges are stored in both directions.
+#[derive(Debug, Clone, Default)]
+pub struct MultilayerGraph {
+    nodes: Interner,
+    layer_names: Interner,
+    layers: Vec<HashMap<usize, HashMap<usize, usize>>>,
+}
+
+impl MultilayerGraph {
+    pub fn new() -> Self {
+        MultilayerGraph {
+            nodes: Interner::new(),
+            layer_names: Interner::new(),
+            layers: Vec::new(),
+        }
+    }
+
+    pub fn add_layer(&mut self, name: &str) -> usize {
+        let id = self.layer_names.intern(name);
+        if id == self.layers.len() {
+            self.layers.push(HashMap::new());
+        }
+        id
+    }
+
+    pub fn add_node(&mut self, label: &str) -> usize {
+        self.nodes.intern(label)
+    }
+
+    /// Sets the weight of the undirected edge `a - b` in `layer`. Self loops are ignored and a
+    /// repeated edge keeps the last weight.
+    pub fn add_edge(&mut self, layer: usize, a: usize, b: usize, weight: usize) {
+        if a == b {
+            return;
+        }
+        let matrix = &mut self.layers[layer];
+        matrix.entry(a).or_default().insert(b, weight);
+        matrix.entry(b).or_default().insert(a, weight);
+    }
+
+    pub fn add_labeled_edge(&mut self, layer: &str, a: &str, b: &str, weight: usize) {
+        let layer = self.add_layer(layer);
+        let a = self.add_node(a);
+        let b = self.add_node(b);
+        self.add_edge(layer, a, b, weight);
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn node_id(&self, label: &str) -> Option<usize> {
+        self.nodes.id(label)
+    }
+
+    pub fn node_label(&self, node: usize) -> &str {
+        self.nodes.label(node)
+    }
+
+    pub fn layer_id(&self, name: &str) -> Option<usize> {
+        self.layer_names.id(name)
+    }
+
+    pub fn layer_name(&self, layer: usize) -> &str {
+        self.layer_names.label(layer)
+    }
+
+    pub fn layer(&self, layer: usize) -> &HashMap<usize, HashMap<usize, usize>> {
+        &self.layers[layer]
+    }
+
+    pub fn all_layers(&self) -> Vec<usize> {
+        (0..self.layers.len()).collect()
+    }
+
+    /// Neighbors of `node` in `layer` with edge weights, `None` if the node has no edge there.
+    pub fn neighbors(&self, node: usize, layer: usize) -> Option<&HashMap<usize, usize>> {
+        self.layers[layer].get(&node)
+    }
+
+    pub fn degree(&self, node: usize, layer: usize) -> usize {
+        self.neighbors(node, layer).map_or(0, |n| n.len())
+    }
+
+    pub fn edge_count(&self, layer: usize) -> usize {
+        self.layers[layer].values().map(|v| v.len()).sum::<usize>() / 2
+    }
+
+    /// Lines `layer node node weight` separated by whitespace, as in `lazega.edges`.
+    pub fn load_lazega(path: &str) -> Self {
+        let mut graph = MultilayerGraph::new();
+        for line in read_to_string(path).unwrap().lines() {
+            let mut iter = line.split_whitespace();
+            let layer = match iter.next() {
+                Some(layer) => layer,
+                None => continue,
+            };
+            let a = iter.next().unwrap();
+            let b = iter.next().unwrap();
+            let weight = iter
+                .next()
+                .map_or(1, |w| w.parse::<f64>().unwrap() as usize);
+            graph.add_labeled_edge(layer, a, b, weight);
+        }
+        graph
+    }
+
+    /// Lines `node,node,layer`, as in `aucs.edges`. All edges get weight 1.
+    pub fn load_aucs(path: &str) -> Self {
+        let mut graph = MultilayerGraph::new();
+        for line in read_to_string(path).unwrap().lines() {
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            if fields.len() < 3 {
+                continue;
+            }
+            graph.add_labeled_edge(fields[2], fields[0], fields[1], 1);
+        }
+        graph
+    }
+}
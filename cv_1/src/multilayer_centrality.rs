@@ -0,0 +1,112 @@
+use std::fs::write;
+
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rayon_hash::HashSet;
+
+use crate::multilayer::MultilayerGraph;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultilayerCentrality {
+    pub node: usize,
+    pub degree: usize,
+    pub degree_deviation: f64,
+    pub neighborhood: usize,
+    pub connective_redundancy: f64,
+    pub exclusive_neighborhood: usize,
+}
+
+/// Union of the neighbors of `node` over `layers`.
+pub fn neighbors_in(graph: &MultilayerGraph, node: usize, layers: &[usize]) -> HashSet<usize> {
+    let mut result: HashSet<usize> = HashSet::new();
+    for &layer in layers {
+        if let Some(neighbors) = graph.neighbors(node, layer) {
+            result.extend(neighbors.keys().cloned());
+        }
+    }
+    result
+}
+
+/// Number of edges of `node` summed over `layers`.
+pub fn degree_centrality(graph: &MultilayerGraph, node: usize, layers: &[usize]) -> usize {
+    layers.iter().map(|&layer| graph.degree(node, layer)).sum()
+}
+
+/// Sample standard deviation of the degree of `node` over the layers it appears in.
+pub fn degree_deviation(graph: &MultilayerGraph, node: usize) -> f64 {
+    let degrees: Vec<f64> = graph
+        .all_layers()
+        .into_iter()
+        .filter_map(|layer| graph.neighbors(node, layer).map(|n| n.len() as f64))
+        .collect();
+    if degrees.len() < 2 {
+        return 0.0;
+    }
+    let mean = degrees.iter().sum::<f64>() / degrees.len() as f64;
+    let variance =
+        degrees.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (degrees.len() - 1) as f64;
+    variance.sqrt()
+}
+
+pub fn neighborhood_centrality(graph: &MultilayerGraph, node: usize, layers: &[usize]) -> usize {
+    neighbors_in(graph, node, layers).len()
+}
+
+/// `1 - neighborhood / degree`, 0 for nodes without edges in `layers`.
+pub fn connective_redundancy(graph: &MultilayerGraph, node: usize, layers: &[usize]) -> f64 {
+    let degree = degree_centrality(graph, node, layers);
+    if degree == 0 {
+        return 0.0;
+    }
+    1.0 - neighborhood_centrality(graph, node, layers) as f64 / degree as f64
+}
+
+/// Neighbors of `node` in `layers` that are not its neighbors in any other layer.
+pub fn exclusive_neighborhood(
+    graph: &MultilayerGraph,
+    node: usize,
+    layers: &[usize],
+) -> HashSet<usize> {
+    let other_layers: Vec<usize> = graph
+        .all_layers()
+        .into_iter()
+        .filter(|layer| !layers.contains(layer))
+        .collect();
+    let others = neighbors_in(graph, node, &other_layers);
+    neighbors_in(graph, node, layers)
+        .into_iter()
+        .filter(|n| !others.contains(n))
+        .collect()
+}
+
+/// All centralities for every node and the given layer subset, sorted by node id.
+pub fn centrality_table(graph: &MultilayerGraph, layers: &[usize]) -> Vec<MultilayerCentrality> {
+    (0..graph.node_count())
+        .into_par_iter()
+        .map(|node| MultilayerCentrality {
+            node,
+            degree: degree_centrality(graph, node, layers),
+            degree_deviation: degree_deviation(graph, node),
+            neighborhood: neighborhood_centrality(graph, node, layers),
+            connective_redundancy: connective_redundancy(graph, node, layers),
+            exclusive_neighborhood: exclusive_neighborhood(graph, node, layers).len(),
+        })
+        .collect()
+}
+
+pub fn write_centrality_table(path: &str, graph: &MultilayerGraph, table: &[MultilayerCentrality]) {
+    let mut lines = vec![String::from(
+        "node,degree,degree_deviation,neighborhood,connective_redundancy,exclusive_neighborhood",
+    )];
+    for row in table {
+        lines.push(format!(
+            "{},{},{},{},{},{}",
+            graph.node_label(row.node),
+            row.degree,
+            row.degree_deviation,
+            row.neighborhood,
+            row.connective_redundancy,
+            row.exclusive_neighborhood
+        ));
+    }
+    write(path, lines.join("\n").as_bytes()).unwrap();
+}
@@ -0,0 +1,322 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rayon_hash::HashMap;
+
+use crate::louvain::{renumber, Level};
+use crate::multilayer::MultilayerGraph;
+use crate::partition::Partition;
+
+const MIN_GAIN: f64 = 1e-12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MultisliceConfig {
+    // gamma, the same for every layer
+    pub resolution: f64,
+    // omega, weight of the edges between the copies of a node in different layers
+    pub coupling: f64,
+    pub seed: u64,
+    pub max_levels: usize,
+    pub max_passes: usize,
+}
+
+impl Default for MultisliceConfig {
+    fn default() -> Self {
+        MultisliceConfig {
+            resolution: 1.0,
+            coupling: 1.0,
+            seed: 420,
+            max_levels: 100,
+            max_passes: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MultisliceResult {
+    // Membership of the nodes of every layer, community ids are shared across layers
+    pub memberships: Vec<Partition>,
+    pub modularity: f64,
+    pub levels: usize,
+}
+
+impl MultisliceResult {
+    pub fn community_count(&self) -> usize {
+        self.memberships
+            .iter()
+            .flat_map(|p| p.membership().values().cloned())
+            .max()
+            .map_or(0, |c| c + 1)
+    }
+
+    /// Share of the nodes present in both layers that are in the same community in both.
+    pub fn persistence(&self, layer1: usize, layer2: usize) -> f64 {
+        let a = &self.memberships[layer1];
+        let b = &self.memberships[layer2];
+        let mut common = 0.0;
+        let mut same = 0.0;
+        for (&node, &community) in a.membership().iter() {
+            if let Some(other) = b.community_of(node) {
+                common += 1.0;
+                if other == community {
+                    same += 1.0;
+                }
+            }
+        }
+        if common == 0.0 {
+            0.0
+        } else {
+            same / common
+        }
+    }
+}
+
+/// Supra-graph of the (node, layer) pairs where the node has an edge in the layer. Intra-layer
+/// edges keep their weight and every pair of copies of a node is joined with `coupling`
+/// (categorical coupling, layers are unordered).
+struct SupraGraph {
+    // (node, layer) of every supra node, sorted by layer then node
+    pairs: Vec<(usize, usize)>,
+    level: Level,
+    // degree inside its own layer, the only part entering the null model
+    layer_degrees: Vec<f64>,
+    // 2 m_s of every layer
+    layer_volumes: Vec<f64>,
+    // sum of all edge weights counted from both ends, coupling included
+    total: f64,
+}
+
+impl SupraGraph {
+    fn new(graph: &MultilayerGraph, coupling: f64) -> Self {
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        for layer in graph.all_layers() {
+            let mut nodes: Vec<usize> = graph.layer(layer).keys().cloned().collect();
+            nodes.sort_unstable();
+            pairs.extend(nodes.into_iter().map(|node| (node, layer)));
+        }
+        let index: HashMap<(usize, usize), usize> =
+            pairs.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+
+        let mut copies: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (i, &(node, _)) in pairs.iter().enumerate() {
+            copies.entry(node).or_default().push(i);
+        }
+
+        let mut layer_volumes = vec![0.0; graph.layer_count()];
+        let mut layer_degrees = vec![0.0; pairs.len()];
+        let mut neighbors: Vec<Vec<(usize, f64)>> = Vec::with_capacity(pairs.len());
+        for (i, &(node, layer)) in pairs.iter().enumerate() {
+            let mut list: Vec<(usize, f64)> = graph.layer(layer)[&node]
+                .iter()
+                .map(|(neighbor, &weight)| (index[&(*neighbor, layer)], weight as f64))
+                .collect();
+            layer_degrees[i] = list.iter().map(|&(_, w)| w).sum();
+            layer_volumes[layer] += layer_degrees[i];
+            list.extend(
+                copies[&node]
+                    .iter()
+                    .filter(|&&copy| copy != i)
+                    .map(|&copy| (copy, coupling)),
+            );
+            list.sort_by_key(|a| a.0);
+            neighbors.push(list);
+        }
+
+        let degrees: Vec<f64> = neighbors
+            .iter()
+            .map(|list| list.iter().map(|&(_, w)| w).sum())
+            .collect();
+        let total = degrees.iter().sum();
+        SupraGraph {
+            pairs,
+            level: Level {
+                self_loops: vec![0.0; neighbors.len()],
+                neighbors,
+                degrees,
+            },
+            layer_degrees,
+            layer_volumes,
+            total,
+        }
+    }
+}
+
+/// Local moving of generalized Louvain: `null[v][s]` is the degree of (aggregated) node `v` in
+/// layer `s`, so the expected weight only counts within each layer.
+fn local_moving(
+    level: &Level,
+    null: &[Vec<f64>],
+    layer_volumes: &[f64],
+    community: &mut [usize],
+    config: &MultisliceConfig,
+    rng: &mut StdRng,
+) -> bool {
+    let n = level.len();
+    let layers = layer_volumes.len();
+    let mut totals = vec![vec![0.0; layers]; n];
+    for node in 0..n {
+        for s in 0..layers {
+            totals[community[node]][s] += null[node][s];
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(rng);
+
+    let mut weights_to = vec![0.0; n];
+    let mut touched: Vec<usize> = Vec::new();
+    let mut moved_any = false;
+
+    for _ in 0..config.max_passes {
+        let mut moved = false;
+        for &node in order.iter() {
+            let current = community[node];
+            for s in 0..layers {
+                totals[current][s] -= null[node][s];
+            }
+
+            for &(neighbor, weight) in level.neighbors[node].iter() {
+                let c = community[neighbor];
+                if weights_to[c] == 0.0 {
+                    touched.push(c);
+                }
+                weights_to[c] += weight;
+            }
+
+            let gain = |c: usize, w: f64| {
+                let expected: f64 = (0..layers)
+                    .filter(|&s| layer_volumes[s] > 0.0)
+                    .map(|s| null[node][s] * totals[c][s] / layer_volumes[s])
+                    .sum();
+                w - config.resolution * expected
+            };
+            let mut best = current;
+            let mut best_gain = gain(current, weights_to[current]);
+            for &c in touched.iter() {
+                let g = gain(c, weights_to[c]);
+                if g > best_gain + MIN_GAIN {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+
+            for &c in touched.iter() {
+                weights_to[c] = 0.0;
+            }
+            touched.clear();
+
+            for s in 0..layers {
+                totals[best][s] += null[node][s];
+            }
+            if best != current {
+                community[node] = best;
+                moved = true;
+                moved_any = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    moved_any
+}
+
+/// Multislice modularity `Q = 1/2mu sum [(A_ijs - gamma k_is k_js / 2m_s) d_sr + d_ij C_jsr]
+/// d(g_is, g_jr)` of supra node memberships.
+fn multislice_modularity(supra: &SupraGraph, membership: &[usize], resolution: f64) -> f64 {
+    if supra.total == 0.0 {
+        return 0.0;
+    }
+    let level = &supra.level;
+    let mut internal = 0.0;
+    for node in 0..level.len() {
+        for &(neighbor, weight) in level.neighbors[node].iter() {
+            if membership[neighbor] == membership[node] {
+                internal += weight;
+            }
+        }
+    }
+    let mut totals: HashMap<(usize, usize), f64> = HashMap::new();
+    for (node, &(_, layer)) in supra.pairs.iter().enumerate() {
+        *totals.entry((membership[node], layer)).or_insert(0.0) += supra.layer_degrees[node];
+    }
+    let expected: f64 = totals
+        .iter()
+        .map(|(&(_, layer), &t)| t * t / supra.layer_volumes[layer])
+        .sum();
+    (internal - resolution * expected) / supra.total
+}
+
+/// Generalized Louvain for the multislice modularity of Mucha et al.: every (node, layer) pair
+/// gets its own community, and `coupling` controls how strongly the copies of a node are pulled
+/// into the same one. `coupling = 0` optimizes every layer on its own, large values converge to
+/// the communities of the flattened graph.
+pub fn multislice_louvain(graph: &MultilayerGraph, config: &MultisliceConfig) -> MultisliceResult {
+    let supra = SupraGraph::new(graph, config.coupling);
+    let layers = graph.layer_count();
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    let mut level = Level {
+        neighbors: supra.level.neighbors.clone(),
+        self_loops: supra.level.self_loops.clone(),
+        degrees: supra.level.degrees.clone(),
+    };
+    let mut null: Vec<Vec<f64>> = supra
+        .pairs
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, layer))| {
+            let mut row = vec![0.0; layers];
+            row[layer] = supra.layer_degrees[i];
+            row
+        })
+        .collect();
+    let mut node_level: Vec<usize> = (0..supra.pairs.len()).collect();
+    let mut community: Vec<usize> = (0..supra.pairs.len()).collect();
+    let mut levels = 0;
+
+    while levels < config.max_levels && level.len() > 0 {
+        let moved = local_moving(
+            &level,
+            &null,
+            &supra.layer_volumes,
+            &mut community,
+            config,
+            &mut rng,
+        );
+        let count = renumber(&mut community);
+        levels += 1;
+        if !moved || count == level.len() {
+            break;
+        }
+
+        let mut aggregated = vec![vec![0.0; layers]; count];
+        for (node, row) in null.iter().enumerate() {
+            for s in 0..layers {
+                aggregated[community[node]][s] += row[s];
+            }
+        }
+        null = aggregated;
+        level = level.aggregate(&community, count);
+        for node in node_level.iter_mut() {
+            *node = community[*node];
+        }
+        community = (0..count).collect();
+    }
+
+    let mut membership: Vec<usize> = node_level.iter().map(|&node| community[node]).collect();
+    renumber(&mut membership);
+    let modularity = multislice_modularity(&supra, &membership, config.resolution);
+
+    let mut memberships: Vec<HashMap<usize, usize>> = vec![HashMap::new(); layers];
+    for (&(node, layer), &c) in supra.pairs.iter().zip(membership.iter()) {
+        memberships[layer].insert(node, c);
+    }
+
+    MultisliceResult {
+        memberships: memberships.into_iter().map(Partition::new).collect(),
+        modularity,
+        levels,
+    }
+}
@@ -0,0 +1,100 @@
+use std::fs::{read_to_string, write};
+
+use rayon_hash::HashMap;
+
+/// Assignment of nodes to non-overlapping communities, keyed by the node ids of the sparse matrix
+/// the partition was computed on.
+#[derive(Debug, Clone, Default)]
+pub struct Partition {
+    membership: HashMap<usize, usize>,
+}
+
+impl Partition {
+    pub fn new(membership: HashMap<usize, usize>) -> Self {
+        Partition { membership }
+    }
+
+    pub fn from_communities(communities: &[Vec<usize>]) -> Self {
+        let mut membership: HashMap<usize, usize> = HashMap::new();
+        for (community, nodes) in communities.iter().enumerate() {
+            for &node in nodes {
+                membership.insert(node, community);
+            }
+        }
+        Partition { membership }
+    }
+
+    pub fn membership(&self) -> &HashMap<usize, usize> {
+        &self.membership
+    }
+
+    pub fn community_of(&self, node: usize) -> Option<usize> {
+        self.membership.get(&node).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.membership.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.membership.is_empty()
+    }
+
+    /// Members of every community, largest first, ties broken by the smallest member.
+    pub fn communities(&self) -> Vec<Vec<usize>> {
+        let mut grouped: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (&node, &community) in self.membership.iter() {
+            grouped.entry(community).or_default().push(node);
+        }
+        let mut communities: Vec<Vec<usize>> = grouped
+            .into_iter()
+            .map(|(_, mut nodes)| {
+                nodes.sort_unstable();
+                nodes
+            })
+            .collect();
+        communities.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+        communities
+    }
+
+    pub fn community_count(&self) -> usize {
+        self.communities().len()
+    }
+
+    /// Same grouping with community ids renumbered `0..k` in the order of `communities()`.
+    pub fn normalized(&self) -> Partition {
+        Partition::from_communities(&self.communities())
+    }
+
+    /// Reads lines `node community` as written by `write`, e.g. an igraph membership exported
+    /// from `cv_6.py`.
+    pub fn read(path: &str) -> Self {
+        let mut membership: HashMap<usize, usize> = HashMap::new();
+        for line in read_to_string(path).unwrap().lines() {
+            let mut iter = line.split_whitespace();
+            if let (Some(node), Some(community)) = (iter.next(), iter.next()) {
+                membership.insert(node.parse().unwrap(), community.parse().unwrap());
+            }
+        }
+        Partition { membership }
+    }
+
+    /// Lines `node community`, sorted by node.
+    pub fn write(&self, path: &str) {
+        let mut rows: Vec<(usize, usize)> = self
+            .membership
+            .iter()
+            .map(|(&node, &community)| (node, community))
+            .collect();
+        rows.sort_unstable();
+        write(
+            path,
+            rows.iter()
+                .map(|(node, community)| format!("{} {}", node, community))
+                .collect::<Vec<String>>()
+                .join("\n")
+                .as_bytes(),
+        )
+        .unwrap();
+    }
+}
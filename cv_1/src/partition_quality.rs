@@ -0,0 +1,268 @@
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rayon_hash::{HashMap, HashSet};
+
+use crate::graph::IndexedGraph;
+use crate::partition::Partition;
+
+/// Modularity `sum_c in_c / 2m - resolution * (tot_c / 2m)^2` of `membership` (indexed like
+/// `graph`).
+pub fn indexed_modularity(graph: &IndexedGraph, membership: &[usize], resolution: f64) -> f64 {
+    let m2 = 2.0 * graph.total_weight();
+    if m2 == 0.0 {
+        return 0.0;
+    }
+    let internal: f64 = (0..graph.len())
+        .into_par_iter()
+        .map(|node| {
+            graph.neighbors[node]
+                .iter()
+                .filter(|&&(neighbor, _)| membership[neighbor] == membership[node])
+                .map(|&(_, w)| w)
+                .sum::<f64>()
+        })
+        .sum();
+    let mut totals: HashMap<usize, f64> = HashMap::new();
+    for (node, &community) in membership.iter().enumerate() {
+        *totals.entry(community).or_insert(0.0) += graph.strength(node);
+    }
+    let expected: f64 = totals.values().map(|t| (t / m2).powi(2)).sum();
+    internal / m2 - resolution * expected
+}
+
+/// Community of every node of `graph`. Nodes missing from the partition get a singleton
+/// community each.
+pub fn indexed_membership(graph: &IndexedGraph, partition: &Partition) -> Vec<usize> {
+    let mut next = partition
+        .membership()
+        .values()
+        .cloned()
+        .max()
+        .map_or(0, |m| m + 1);
+    graph
+        .ids
+        .iter()
+        .map(|id| {
+            partition.community_of(*id).unwrap_or_else(|| {
+                next += 1;
+                next - 1
+            })
+        })
+        .collect()
+}
+
+pub fn modularity(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    partition: &Partition,
+    resolution: f64,
+) -> f64 {
+    let graph = IndexedGraph::new(sparse_matrix);
+    indexed_modularity(&graph, &indexed_membership(&graph, partition), resolution)
+}
+
+/// Weighted conductance `cut(S) / min(vol(S), vol(V \ S))` of every community, keyed by
+/// community id. Communities without any edge get 0.
+pub fn conductance(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    partition: &Partition,
+) -> HashMap<usize, f64> {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let membership = indexed_membership(&graph, partition);
+
+    // community -> (volume, cut)
+    let mut stats: HashMap<usize, (f64, f64)> = HashMap::new();
+    for node in 0..graph.len() {
+        let community = membership[node];
+        let cut: f64 = graph.neighbors[node]
+            .iter()
+            .filter(|&&(neighbor, _)| membership[neighbor] != community)
+            .map(|&(_, w)| w)
+            .sum();
+        let entry = stats.entry(community).or_insert((0.0, 0.0));
+        entry.0 += graph.strength(node);
+        entry.1 += cut;
+    }
+
+    let known: HashSet<usize> = partition.membership().values().cloned().collect();
+    let total_volume = 2.0 * graph.total_weight();
+    stats
+        .into_iter()
+        .filter(|(community, _)| known.contains(community))
+        .map(|(community, (volume, cut))| {
+            let denominator = volume.min(total_volume - volume);
+            let value = if denominator == 0.0 {
+                0.0
+            } else {
+                cut / denominator
+            };
+            (community, value)
+        })
+        .collect()
+}
+
+/// Share of the total edge weight that falls inside communities.
+pub fn coverage(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    partition: &Partition,
+) -> f64 {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let membership = indexed_membership(&graph, partition);
+    let total = 2.0 * graph.total_weight();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let internal: f64 = (0..graph.len())
+        .into_par_iter()
+        .map(|node| {
+            graph.neighbors[node]
+                .iter()
+                .filter(|&&(neighbor, _)| membership[neighbor] == membership[node])
+                .map(|&(_, w)| w)
+                .sum::<f64>()
+        })
+        .sum();
+    internal / total
+}
+
+/// Share of node pairs classified correctly: intra-community pairs joined by an edge plus
+/// inter-community pairs without one. Edge weights are ignored.
+pub fn performance(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    partition: &Partition,
+) -> f64 {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let membership = indexed_membership(&graph, partition);
+    let n = graph.len() as f64;
+    if graph.len() < 2 {
+        return 0.0;
+    }
+
+    let (intra_edges, inter_edges) = (0..graph.len())
+        .into_par_iter()
+        .map(|node| {
+            let mut intra = 0usize;
+            let mut inter = 0usize;
+            for &(neighbor, _) in graph.neighbors[node].iter() {
+                if neighbor < node {
+                    continue;
+                }
+                if membership[neighbor] == membership[node] {
+                    intra += 1;
+                } else {
+                    inter += 1;
+                }
+            }
+            (intra, inter)
+        })
+        .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+
+    let mut sizes: HashMap<usize, f64> = HashMap::new();
+    for &community in membership.iter() {
+        *sizes.entry(community).or_insert(0.0) += 1.0;
+    }
+    let all_pairs = n * (n - 1.0) / 2.0;
+    let intra_pairs: f64 = sizes.values().map(|s| s * (s - 1.0) / 2.0).sum();
+    let inter_non_edges = all_pairs - intra_pairs - inter_edges as f64;
+
+    (intra_edges as f64 + inter_non_edges) / all_pairs
+}
+
+/// Contingency counts of the nodes present in both partitions.
+fn contingency(a: &Partition, b: &Partition) -> (HashMap<(usize, usize), f64>, f64) {
+    let mut table: HashMap<(usize, usize), f64> = HashMap::new();
+    let mut n = 0.0;
+    for (node, &ca) in a.membership().iter() {
+        if let Some(cb) = b.community_of(*node) {
+            *table.entry((ca, cb)).or_insert(0.0) += 1.0;
+            n += 1.0;
+        }
+    }
+    (table, n)
+}
+
+fn marginals(table: &HashMap<(usize, usize), f64>) -> (HashMap<usize, f64>, HashMap<usize, f64>) {
+    let mut rows: HashMap<usize, f64> = HashMap::new();
+    let mut columns: HashMap<usize, f64> = HashMap::new();
+    for (&(a, b), &count) in table.iter() {
+        *rows.entry(a).or_insert(0.0) += count;
+        *columns.entry(b).or_insert(0.0) += count;
+    }
+    (rows, columns)
+}
+
+fn entropy(counts: &HashMap<usize, f64>, n: f64) -> f64 {
+    counts
+        .values()
+        .map(|&c| {
+            let p = c / n;
+            -p * p.ln()
+        })
+        .sum()
+}
+
+/// Normalized mutual information `I(A; B) / ((H(A) + H(B)) / 2)` over the common nodes. Two
+/// single-community partitions are considered identical.
+pub fn normalized_mutual_information(a: &Partition, b: &Partition) -> f64 {
+    let (table, n) = contingency(a, b);
+    if n == 0.0 {
+        return 0.0;
+    }
+    let (rows, columns) = marginals(&table);
+    let mutual: f64 = table
+        .iter()
+        .map(|(&(ca, cb), &count)| {
+            let p = count / n;
+            p * (p / (rows[&ca] / n * columns[&cb] / n)).ln()
+        })
+        .sum();
+    let h = (entropy(&rows, n) + entropy(&columns, n)) / 2.0;
+    if h == 0.0 {
+        return 1.0;
+    }
+    mutual / h
+}
+
+/// Adjusted Rand index over the common nodes.
+pub fn adjusted_rand_index(a: &Partition, b: &Partition) -> f64 {
+    let (table, n) = contingency(a, b);
+    let pairs = |x: f64| x * (x - 1.0) / 2.0;
+    if n < 2.0 {
+        return 1.0;
+    }
+    let (rows, columns) = marginals(&table);
+    let index: f64 = table.values().map(|&c| pairs(c)).sum();
+    let sum_rows: f64 = rows.values().map(|&c| pairs(c)).sum();
+    let sum_columns: f64 = columns.values().map(|&c| pairs(c)).sum();
+    let expected = sum_rows * sum_columns / pairs(n);
+    let maximum = (sum_rows + sum_columns) / 2.0;
+    if maximum == expected {
+        return 1.0;
+    }
+    (index - expected) / (maximum - expected)
+}
+
+pub fn partition_report(
+    name: &str,
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    partition: &Partition,
+) {
+    let start = std::time::Instant::now();
+    let modularity = modularity(sparse_matrix, partition, 1.0);
+    let coverage = coverage(sparse_matrix, partition);
+    let performance = performance(sparse_matrix, partition);
+    let conductances = conductance(sparse_matrix, partition);
+    let avg_conductance = if conductances.is_empty() {
+        0.0
+    } else {
+        conductances.values().sum::<f64>() / conductances.len() as f64
+    };
+    let end = std::time::Instant::now();
+    println!(
+        "{}: modularity {}, coverage {}, performance {}, average conductance {} in {}",
+        name,
+        modularity,
+        coverage,
+        performance,
+        avg_conductance,
+        (end - start).as_millis()
+    );
+}
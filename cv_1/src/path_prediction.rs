@@ -0,0 +1,445 @@
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+use crate::graph::IndexedGraph;
+use crate::link_evaluation::{
+    evaluate_candidates_by, k_fold_splits, print_summary, LinkMetrics, SplitCandidates,
+};
+
+// Held out edges and non-edges sampled per fold for the path cross validation, every sampled
+// pair costs a row of the root
+const PATH_SAMPLES: usize = 1000;
+
+/// Path-based similarity indices. Walk counts use the edge weights, on the unweighted SNAP
+/// graphs they are plain path counts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathIndex {
+    // sum_{l=1..max_length} beta^l (A^l)_ab
+    TruncatedKatz {
+        beta: f64,
+        max_length: usize,
+    },
+    // ((I - beta A)^-1 - I)_ab solved per row, needs beta < 1 / spectral radius. tolerance is on
+    // the relative change of an iteration, max_nodes caps the entries propagated per iteration
+    Katz {
+        beta: f64,
+        tolerance: f64,
+        max_iterations: usize,
+        max_nodes: usize,
+    },
+    // (A^2 + epsilon A^3)_ab
+    LocalPath {
+        epsilon: f64,
+    },
+    // Monte Carlo estimate of E[decay^meeting time] of two random walks
+    SimRank {
+        decay: f64,
+        walks: usize,
+        max_steps: usize,
+        seed: u64,
+    },
+    // Visit probability of b for a random walk from a that restarts at a, epsilon is the
+    // residual per unit of strength left unpushed
+    RootedPageRank {
+        restart: f64,
+        epsilon: f64,
+    },
+}
+
+impl PathIndex {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PathIndex::TruncatedKatz { .. } => "truncated katz",
+            PathIndex::Katz { .. } => "katz",
+            PathIndex::LocalPath { .. } => "local path",
+            PathIndex::SimRank { .. } => "simrank",
+            PathIndex::RootedPageRank { .. } => "rooted pagerank",
+        }
+    }
+}
+
+/// Upper bound on the spectral radius of the adjacency matrix (the maximum strength), `beta`
+/// below its inverse always makes the Katz series converge.
+pub fn spectral_radius_bound(graph: &IndexedGraph) -> f64 {
+    (0..graph.len())
+        .map(|node| graph.strength(node))
+        .fold(0.0, f64::max)
+}
+
+/// `vector * A` on a sparse vector, only the reached nodes are stored.
+fn step(graph: &IndexedGraph, vector: &HashMap<usize, f64>) -> HashMap<usize, f64> {
+    let mut result: HashMap<usize, f64> = HashMap::new();
+    for (&node, &value) in vector.iter() {
+        for &(neighbor, weight) in graph.neighbors[node].iter() {
+            *result.entry(neighbor).or_insert(0.0) += value * weight;
+        }
+    }
+    result
+}
+
+/// Weighted sum `sum_l coefficients[l - 1] * (A^l)_a` of the first walk lengths, sparse. Memory
+/// grows with the nodes reachable in `coefficients.len()` steps from `source`.
+fn walk_row(graph: &IndexedGraph, source: usize, coefficients: &[f64]) -> HashMap<usize, f64> {
+    let mut row: HashMap<usize, f64> = HashMap::new();
+    let mut frontier: HashMap<usize, f64> = HashMap::new();
+    frontier.insert(source, 1.0);
+    for &coefficient in coefficients {
+        frontier = step(graph, &frontier);
+        for (&node, &value) in frontier.iter() {
+            *row.entry(node).or_insert(0.0) += coefficient * value;
+        }
+    }
+    row
+}
+
+/// The `count` largest entries of a sparse vector, ties go to the smaller node.
+fn largest(vector: HashMap<usize, f64>, count: usize) -> HashMap<usize, f64> {
+    if count == 0 {
+        return HashMap::new();
+    }
+    let mut entries: Vec<(usize, f64)> = vector.into_iter().collect();
+    entries.select_nth_unstable_by(count - 1, |a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    entries.truncate(count);
+    entries.into_iter().collect()
+}
+
+/// Katz row of `source`, `((I - beta A)^-1 - I)_a`, by Jacobi iteration of `x = beta A (e_a + x)`
+/// on a sparse vector. The change of every iteration is `beta A` times the previous change, the
+/// iteration stops once its L1 norm is below `tolerance` times the L1 norm of the row. Returns the
+/// row and whether it converged within `max_iterations`. Only the `max_nodes` largest entries of a
+/// change are propagated, the others still count, which bounds the cost of a row through hubs.
+fn katz_row(
+    graph: &IndexedGraph,
+    source: usize,
+    beta: f64,
+    tolerance: f64,
+    max_iterations: usize,
+    max_nodes: usize,
+) -> (HashMap<usize, f64>, bool) {
+    let mut row: HashMap<usize, f64> = HashMap::new();
+    let mut change: HashMap<usize, f64> = HashMap::new();
+    change.insert(source, 1.0);
+    for _ in 0..max_iterations {
+        change = step(graph, &change);
+        for (&node, value) in change.iter_mut() {
+            *value *= beta;
+            *row.entry(node).or_insert(0.0) += *value;
+        }
+        let norm: f64 = change.values().sum();
+        if norm <= tolerance * row.values().sum::<f64>() {
+            return (row, true);
+        }
+        if change.len() > max_nodes {
+            change = largest(change, max_nodes);
+        }
+    }
+    (row, false)
+}
+
+/// Personalized PageRank vector of `source` by the push method of Andersen, Chung and Lang: a
+/// node pushes `restart` of its residual into its score and spreads the rest to its neighbors
+/// while the residual is at least `epsilon` times its strength. Every score is within `epsilon`
+/// times the node strength of the exact value. Mass on nodes without edges goes back to the root.
+fn rooted_pagerank_row(
+    graph: &IndexedGraph,
+    source: usize,
+    restart: f64,
+    epsilon: f64,
+) -> HashMap<usize, f64> {
+    let mut row: HashMap<usize, f64> = HashMap::new();
+    if graph.strength(source) == 0.0 {
+        row.insert(source, 1.0);
+        return row;
+    }
+    let mut residual: HashMap<usize, f64> = HashMap::new();
+    residual.insert(source, 1.0);
+    let mut queue = VecDeque::from([source]);
+    let add = |residual: &mut HashMap<usize, f64>, queue: &mut VecDeque<usize>, node, value| {
+        let threshold = epsilon * graph.strength(node);
+        let entry = residual.entry(node).or_insert(0.0);
+        let before = *entry;
+        *entry += value;
+        if before < threshold && *entry >= threshold {
+            queue.push_back(node);
+        }
+    };
+    while let Some(node) = queue.pop_front() {
+        let value = residual.insert(node, 0.0).unwrap_or(0.0);
+        *row.entry(node).or_insert(0.0) += restart * value;
+        let spread = (1.0 - restart) * value;
+        let strength = graph.strength(node);
+        if strength == 0.0 {
+            add(&mut residual, &mut queue, source, spread);
+            continue;
+        }
+        for &(neighbor, weight) in graph.neighbors[node].iter() {
+            add(
+                &mut residual,
+                &mut queue,
+                neighbor,
+                spread * weight / strength,
+            );
+        }
+    }
+    row
+}
+
+fn random_neighbor(graph: &IndexedGraph, node: usize, rng: &mut StdRng) -> Option<usize> {
+    let neighbors = &graph.neighbors[node];
+    if neighbors.is_empty() {
+        None
+    } else {
+        Some(neighbors[rng.gen_range(0..neighbors.len())].0)
+    }
+}
+
+/// Fraction-of-meetings estimate of SimRank: two walks start at `a` and `b` and move together,
+/// a meeting after `t` steps adds `decay^t`. Needs constant memory per pair.
+fn simrank_pair(
+    graph: &IndexedGraph,
+    a: usize,
+    b: usize,
+    decay: f64,
+    walks: usize,
+    max_steps: usize,
+    rng: &mut StdRng,
+) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let mut total = 0.0;
+    for _ in 0..walks {
+        let (mut x, mut y) = (a, b);
+        for t in 1..=max_steps {
+            match (
+                random_neighbor(graph, x, rng),
+                random_neighbor(graph, y, rng),
+            ) {
+                (Some(nx), Some(ny)) => {
+                    x = nx;
+                    y = ny;
+                }
+                _ => break,
+            }
+            if x == y {
+                total += decay.powi(t as i32);
+                break;
+            }
+        }
+    }
+    total / walks as f64
+}
+
+/// Scores index pairs `(a, b)` with `a` as the root of the row based indices. Pairs are grouped
+/// by `a` and the groups are scored in parallel, so at most one sparse row per thread is alive.
+pub fn score_path_pairs(
+    graph: &IndexedGraph,
+    index: PathIndex,
+    pairs: &[(usize, usize)],
+) -> Vec<f64> {
+    if let PathIndex::SimRank {
+        decay,
+        walks,
+        max_steps,
+        seed,
+    } = index
+    {
+        return (0..pairs.len())
+            .into_par_iter()
+            .map(|i| {
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+                let (a, b) = pairs[i];
+                simrank_pair(graph, a, b, decay, walks, max_steps, &mut rng)
+            })
+            .collect();
+    }
+
+    let mut by_source: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, &(a, _)) in pairs.iter().enumerate() {
+        by_source.entry(a).or_default().push(i);
+    }
+    let mut groups: Vec<(usize, Vec<usize>)> = by_source.into_iter().collect();
+    groups.sort_by_key(|g| g.0);
+
+    let scored: Vec<(Vec<(usize, f64)>, bool)> = groups
+        .par_iter()
+        .map(|(source, members)| {
+            let (row, converged) = match index {
+                PathIndex::TruncatedKatz { beta, max_length } => {
+                    let coefficients: Vec<f64> =
+                        (1..=max_length).map(|l| beta.powi(l as i32)).collect();
+                    (walk_row(graph, *source, &coefficients), true)
+                }
+                PathIndex::LocalPath { epsilon } => {
+                    (walk_row(graph, *source, &[0.0, 1.0, epsilon]), true)
+                }
+                PathIndex::Katz {
+                    beta,
+                    tolerance,
+                    max_iterations,
+                    max_nodes,
+                } => katz_row(graph, *source, beta, tolerance, max_iterations, max_nodes),
+                PathIndex::RootedPageRank { restart, epsilon } => {
+                    (rooted_pagerank_row(graph, *source, restart, epsilon), true)
+                }
+                PathIndex::SimRank { .. } => unreachable!(),
+            };
+            let values = members
+                .iter()
+                .map(|&i| (i, row.get(&pairs[i].1).cloned().unwrap_or(0.0)))
+                .collect();
+            (values, converged)
+        })
+        .collect();
+
+    let stopped = scored.iter().filter(|(_, converged)| !converged).count();
+    if stopped > 0 {
+        println!(
+            "{}: {} of {} rows stopped at max_iterations without converging",
+            index.name(),
+            stopped,
+            scored.len()
+        );
+    }
+    let mut scores = vec![0.0; pairs.len()];
+    for (i, score) in scored.into_iter().flat_map(|(values, _)| values) {
+        scores[i] = score;
+    }
+    scores
+}
+
+/// Scores node id pairs, pairs with a node missing from the graph score 0.
+pub fn score_path_candidates(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    index: PathIndex,
+    candidates: &[(usize, usize)],
+) -> Vec<(usize, usize, f64)> {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let known: Vec<(usize, (usize, usize))> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(
+            |(i, (a, b))| match (graph.index.get(a), graph.index.get(b)) {
+                (Some(&x), Some(&y)) => Some((i, (x, y))),
+                _ => None,
+            },
+        )
+        .collect();
+    let pairs: Vec<(usize, usize)> = known.iter().map(|&(_, p)| p).collect();
+    let scores = score_path_pairs(&graph, index, &pairs);
+
+    let mut result: Vec<(usize, usize, f64)> =
+        candidates.iter().map(|&(a, b)| (a, b, 0.0)).collect();
+    for ((i, _), score) in known.into_iter().zip(scores) {
+        result[i].2 = score;
+    }
+    result
+}
+
+/// `count` random distance-2 pairs (as node ids) for graphs too large to enumerate all of them:
+/// a random node, then a random neighbor of a random neighbor that is not already adjacent.
+pub fn sample_distance_two_pairs(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    count: usize,
+    seed: u64,
+) -> Vec<(usize, usize)> {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pairs = Vec::with_capacity(count);
+    let mut attempts = 0;
+    while pairs.len() < count && attempts < 100 * count && !graph.is_empty() {
+        attempts += 1;
+        let a = rng.gen_range(0..graph.len());
+        let b = match random_neighbor(&graph, a, &mut rng)
+            .and_then(|z| random_neighbor(&graph, z, &mut rng))
+        {
+            Some(b) => b,
+            None => continue,
+        };
+        if a == b || graph.neighbors[a].binary_search_by_key(&b, |e| e.0).is_ok() {
+            continue;
+        }
+        pairs.push((graph.ids[a], graph.ids[b]));
+    }
+    pairs
+}
+
+/// Default parameters used by the reports: Katz `beta` is half the inverse of the spectral
+/// radius bound so the iteration always converges. Katz propagates at most 1000 entries per
+/// iteration, rooted PageRank stops pushing below a residual of `1e-5` per unit of strength.
+pub fn default_indices(graph: &IndexedGraph) -> Vec<PathIndex> {
+    let beta = 0.5 / spectral_radius_bound(graph).max(1.0);
+    vec![
+        PathIndex::TruncatedKatz {
+            beta,
+            max_length: 4,
+        },
+        PathIndex::Katz {
+            beta,
+            tolerance: 1e-4,
+            max_iterations: 100,
+            max_nodes: 1000,
+        },
+        PathIndex::LocalPath { epsilon: 0.01 },
+        PathIndex::SimRank {
+            decay: 0.8,
+            walks: 100,
+            max_steps: 10,
+            seed: 420,
+        },
+        PathIndex::RootedPageRank {
+            restart: 0.15,
+            epsilon: 1e-5,
+        },
+    ]
+}
+
+/// Scores `count` sampled distance-2 pairs with every index.
+pub fn path_prediction_report(sparse_matrix: &HashMap<usize, HashMap<usize, usize>>, count: usize) {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let pairs: Vec<(usize, usize)> = sample_distance_two_pairs(sparse_matrix, count, 420)
+        .iter()
+        .map(|(a, b)| (graph.index[a], graph.index[b]))
+        .collect();
+    for index in default_indices(&graph) {
+        let start = std::time::Instant::now();
+        let scores = score_path_pairs(&graph, index, &pairs);
+        let end = std::time::Instant::now();
+        let average = scores.iter().sum::<f64>() / scores.len().max(1) as f64;
+        println!(
+            "Path index {}: {} pairs, average score {} in {}",
+            index.name(),
+            scores.len(),
+            average,
+            (end - start).as_millis()
+        );
+    }
+}
+
+pub fn path_cross_validation_report(
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    k: usize,
+    seed: u64,
+) {
+    let start = std::time::Instant::now();
+    let indices = default_indices(&IndexedGraph::new(sparse_matrix));
+    let mut metrics: Vec<Vec<LinkMetrics>> = vec![Vec::new(); indices.len()];
+    let folds = k_fold_splits(sparse_matrix, k, seed)
+        .map(|split| SplitCandidates::new(&split, PATH_SAMPLES, seed));
+    for candidates in folds {
+        for (i, &index) in indices.iter().enumerate() {
+            metrics[i].push(evaluate_candidates_by(&candidates, None, |graph, pairs| {
+                score_path_pairs(graph, index, pairs)
+            }));
+        }
+    }
+    for (index, metrics) in indices.iter().zip(metrics) {
+        print_summary(&format!("{}-fold {}", k, index.name()), &metrics);
+    }
+    let end = std::time::Instant::now();
+    println!("Path cross validation in {}", (end - start).as_millis());
+}
@@ -0,0 +1,142 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use crate::multilayer::MultilayerGraph;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomWalkConfig {
+    pub walkers: usize,
+    pub steps: usize,
+    // Probability of moving to another layer of the current node before each step
+    pub switch_probability: f64,
+    pub seed: u64,
+}
+
+impl Default for RandomWalkConfig {
+    fn default() -> Self {
+        RandomWalkConfig {
+            walkers: 1000,
+            steps: 100,
+            switch_probability: 0.5,
+            seed: 420,
+        }
+    }
+}
+
+/// Sorted neighbor lists per layer and node, so walks only depend on the seed and not on hash
+/// map iteration order.
+pub struct WalkIndex {
+    // layer -> node -> neighbors
+    neighbors: Vec<Vec<Vec<usize>>>,
+    // node -> layers the node has at least one edge in
+    node_layers: Vec<Vec<usize>>,
+}
+
+impl WalkIndex {
+    pub fn new(graph: &MultilayerGraph) -> Self {
+        let n = graph.node_count();
+        let mut neighbors = vec![vec![Vec::new(); n]; graph.layer_count()];
+        let mut node_layers = vec![Vec::new(); n];
+        for layer in graph.all_layers() {
+            for (&node, links) in graph.layer(layer).iter() {
+                let mut list: Vec<usize> = links.keys().cloned().collect();
+                list.sort_unstable();
+                neighbors[layer][node] = list;
+            }
+            for (node, layers) in node_layers.iter_mut().enumerate() {
+                if !neighbors[layer][node].is_empty() {
+                    layers.push(layer);
+                }
+            }
+        }
+        WalkIndex {
+            neighbors,
+            node_layers,
+        }
+    }
+
+    pub fn active_nodes(&self) -> Vec<usize> {
+        (0..self.node_layers.len())
+            .filter(|&node| !self.node_layers[node].is_empty())
+            .collect()
+    }
+}
+
+/// Walk of `steps` moves from `start`, returning every visited node including `start`. Before each
+/// move the walker switches, with `switch_probability`, to another layer the current node has
+/// edges in; it always switches when the current layer offers no neighbor.
+pub fn random_walk<R: Rng>(
+    index: &WalkIndex,
+    start: usize,
+    steps: usize,
+    switch_probability: f64,
+    rng: &mut R,
+) -> Vec<usize> {
+    let mut visited = Vec::with_capacity(steps + 1);
+    visited.push(start);
+    let layers = &index.node_layers[start];
+    if layers.is_empty() {
+        return visited;
+    }
+    let mut layer = layers[rng.gen_range(0..layers.len())];
+    let mut node = start;
+
+    for _ in 0..steps {
+        let layers = &index.node_layers[node];
+        let stuck = index.neighbors[layer][node].is_empty();
+        if layers.len() > 1 && (stuck || rng.gen::<f64>() < switch_probability) {
+            let others: Vec<usize> = layers.iter().cloned().filter(|&l| l != layer).collect();
+            layer = others[rng.gen_range(0..others.len())];
+        } else if stuck {
+            layer = layers[0];
+        }
+        let neighbors = &index.neighbors[layer][node];
+        node = neighbors[rng.gen_range(0..neighbors.len())];
+        visited.push(node);
+    }
+
+    visited
+}
+
+/// Share of all walk steps spent in every node. Walker `i` starts at a uniformly random node with
+/// edges and uses its own generator seeded from `seed + i`, so the estimate is reproducible
+/// regardless of how rayon schedules the walkers.
+pub fn occupation_centrality(graph: &MultilayerGraph, config: &RandomWalkConfig) -> Vec<f64> {
+    let index = WalkIndex::new(graph);
+    let starts = index.active_nodes();
+    let n = graph.node_count();
+    if starts.is_empty() || config.walkers == 0 {
+        return vec![0.0; n];
+    }
+
+    let visits = (0..config.walkers)
+        .into_par_iter()
+        .map(|walker| {
+            let mut rng = StdRng::seed_from_u64(config.seed.wrapping_add(walker as u64));
+            let start = starts[rng.gen_range(0..starts.len())];
+            let mut counts = vec![0usize; n];
+            for node in random_walk(
+                &index,
+                start,
+                config.steps,
+                config.switch_probability,
+                &mut rng,
+            ) {
+                counts[node] += 1;
+            }
+            counts
+        })
+        .reduce(
+            || vec![0usize; n],
+            |mut a, b| {
+                for (x, y) in a.iter_mut().zip(b) {
+                    *x += y;
+                }
+                a
+            },
+        );
+
+    let total: usize = visits.iter().sum();
+    visits.iter().map(|&v| v as f64 / total as f64).collect()
+}
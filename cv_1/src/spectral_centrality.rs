@@ -0,0 +1,282 @@
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+use crate::centrality::print_top;
+use crate::graph::IndexedGraph;
+use crate::path_prediction::spectral_radius_bound;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerIterationConfig {
+    // Stops once the L1 change relative to the L1 norm of the vector drops below this
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for PowerIterationConfig {
+    fn default() -> Self {
+        PowerIterationConfig {
+            tolerance: 1e-6,
+            max_iterations: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PowerIterationResult {
+    pub values: Vec<f64>,
+    pub iterations: usize,
+    // Relative L1 change of the last iteration
+    pub error: f64,
+    pub converged: bool,
+}
+
+/// `A x` with the edge weights, every entry pulled from the neighbors in parallel.
+fn multiply(graph: &IndexedGraph, x: &[f64]) -> Vec<f64> {
+    (0..graph.len())
+        .into_par_iter()
+        .map(|v| graph.neighbors[v].iter().map(|&(u, w)| w * x[u]).sum())
+        .collect()
+}
+
+fn l1_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).sum()
+}
+
+fn scale_to(x: &mut [f64], norm: f64) {
+    if norm > 0.0 {
+        for value in x.iter_mut() {
+            *value /= norm;
+        }
+    }
+}
+
+fn l2_norm(x: &[f64]) -> f64 {
+    x.iter().map(|v| v * v).sum::<f64>().sqrt()
+}
+
+/// Repeats `step` from `start` until the relative L1 change drops below the tolerance.
+fn power_iteration<F: Fn(&[f64]) -> Vec<f64>>(
+    start: Vec<f64>,
+    config: &PowerIterationConfig,
+    step: F,
+) -> PowerIterationResult {
+    let mut values = start;
+    let mut error = f64::INFINITY;
+    let mut iterations = 0;
+    while iterations < config.max_iterations {
+        let next = step(&values);
+        let norm: f64 = next.iter().map(|v| v.abs()).sum();
+        error = l1_distance(&next, &values) / norm.max(f64::MIN_POSITIVE);
+        values = next;
+        iterations += 1;
+        if error < config.tolerance {
+            break;
+        }
+    }
+    PowerIterationResult {
+        values,
+        iterations,
+        error,
+        converged: error < config.tolerance,
+    }
+}
+
+/// Weighted PageRank, the random surfer follows an edge with probability `damping` and
+/// otherwise jumps according to `personalization` (one weight per node, normalized to sum 1).
+/// The jump is uniform when `personalization` is `None` or has no positive total. Nodes without
+/// edges jump the same way.
+pub fn pagerank(
+    graph: &IndexedGraph,
+    damping: f64,
+    personalization: Option<&[f64]>,
+    config: &PowerIterationConfig,
+) -> PowerIterationResult {
+    let n = graph.len();
+    let total: f64 = personalization.map_or(0.0, |weights| {
+        assert_eq!(weights.len(), n);
+        weights.iter().sum()
+    });
+    let jump: Vec<f64> = match personalization {
+        Some(weights) if total > 0.0 => weights.iter().map(|w| w / total).collect(),
+        _ => vec![1.0 / n.max(1) as f64; n],
+    };
+    let strengths: Vec<f64> = (0..n).map(|node| graph.strength(node)).collect();
+    power_iteration(jump.clone(), config, |x| {
+        let dangling: f64 = (0..n).filter(|&u| strengths[u] == 0.0).map(|u| x[u]).sum();
+        let spread: Vec<f64> = x
+            .iter()
+            .zip(strengths.iter())
+            .map(|(value, &s)| if s > 0.0 { value / s } else { 0.0 })
+            .collect();
+        let pulled = multiply(graph, &spread);
+        pulled
+            .into_iter()
+            .zip(jump.iter())
+            .map(|(value, p)| damping * value + (damping * dangling + 1.0 - damping) * p)
+            .collect()
+    })
+}
+
+/// Principal eigenvector of `A`, unit L2 norm. The iteration runs on `A + I`, which has the
+/// same eigenvectors but does not oscillate on bipartite components.
+pub fn eigenvector_centrality(
+    graph: &IndexedGraph,
+    config: &PowerIterationConfig,
+) -> PowerIterationResult {
+    let n = graph.len();
+    let start = vec![1.0 / (n.max(1) as f64).sqrt(); n];
+    power_iteration(start, config, |x| {
+        let mut next: Vec<f64> = multiply(graph, x)
+            .into_iter()
+            .zip(x.iter())
+            .map(|(ax, v)| ax + v)
+            .collect();
+        let norm = l2_norm(&next);
+        scale_to(&mut next, norm);
+        next
+    })
+}
+
+/// Rayleigh quotient `x^T A x / x^T x`, the leading eigenvalue for the eigenvector centrality.
+pub fn rayleigh_quotient(graph: &IndexedGraph, x: &[f64]) -> f64 {
+    let ax = multiply(graph, x);
+    let numerator: f64 = ax.iter().zip(x.iter()).map(|(a, b)| a * b).sum();
+    numerator / x.iter().map(|v| v * v).sum::<f64>()
+}
+
+/// Katz centrality `x = alpha A x + beta`, unit L2 norm. Converges only for
+/// `alpha < 1 / lambda_max`.
+pub fn katz_centrality(
+    graph: &IndexedGraph,
+    alpha: f64,
+    beta: f64,
+    config: &PowerIterationConfig,
+) -> PowerIterationResult {
+    let n = graph.len();
+    let mut result = power_iteration(vec![0.0; n], config, |x| {
+        multiply(graph, x)
+            .into_iter()
+            .map(|ax| alpha * ax + beta)
+            .collect()
+    });
+    let norm = l2_norm(&result.values);
+    scale_to(&mut result.values, norm);
+    result
+}
+
+/// HITS `(hubs, authorities)`, both normalized to sum 1 and the iteration count of the hubs.
+/// The graphs here are undirected, so `A^T = A` and both vectors end up equal.
+pub fn hits(
+    graph: &IndexedGraph,
+    config: &PowerIterationConfig,
+) -> (PowerIterationResult, Vec<f64>) {
+    let n = graph.len();
+    let hubs = power_iteration(vec![1.0 / n.max(1) as f64; n], config, |h| {
+        let mut authorities = multiply(graph, h);
+        let total = authorities.iter().sum();
+        scale_to(&mut authorities, total);
+        let mut next = multiply(graph, &authorities);
+        let total = next.iter().sum();
+        scale_to(&mut next, total);
+        next
+    });
+    let mut authorities = multiply(graph, &hubs.values);
+    let total = authorities.iter().sum();
+    scale_to(&mut authorities, total);
+    (hubs, authorities)
+}
+
+fn print_result(
+    name: &str,
+    label: &str,
+    graph: &IndexedGraph,
+    result: &PowerIterationResult,
+    millis: u128,
+    k: usize,
+) {
+    println!(
+        "{} {}: {} iterations, error {:e}, converged {} in {}",
+        name, label, result.iterations, result.error, result.converged, millis
+    );
+    print_top(name, label, graph, &result.values, k, 6);
+}
+
+/// Every spectral centrality with its convergence and the top `k` nodes. The personalized
+/// PageRank restarts at the highest-degree node and Katz uses `alpha = 0.85 / lambda_max`, with
+/// `lambda_max` from the eigenvector when it converged and the maximum strength otherwise.
+pub fn spectral_centrality_report(
+    name: &str,
+    sparse_matrix: &HashMap<usize, HashMap<usize, usize>>,
+    k: usize,
+) {
+    let graph = IndexedGraph::new(sparse_matrix);
+    let config = PowerIterationConfig::default();
+
+    let start = std::time::Instant::now();
+    let result = pagerank(&graph, 0.85, None, &config);
+    let end = std::time::Instant::now();
+    print_result(
+        name,
+        "pagerank",
+        &graph,
+        &result,
+        (end - start).as_millis(),
+        k,
+    );
+
+    if let Some(hub) =
+        (0..graph.len()).max_by_key(|&node| (graph.degree(node), std::cmp::Reverse(node)))
+    {
+        let mut personalization = vec![0.0; graph.len()];
+        personalization[hub] = 1.0;
+        let start = std::time::Instant::now();
+        let result = pagerank(&graph, 0.85, Some(&personalization), &config);
+        let end = std::time::Instant::now();
+        let label = format!("pagerank personalized to {}", graph.ids[hub]);
+        print_result(name, &label, &graph, &result, (end - start).as_millis(), k);
+    }
+
+    let start = std::time::Instant::now();
+    let eigenvector = eigenvector_centrality(&graph, &config);
+    let end = std::time::Instant::now();
+    print_result(
+        name,
+        "eigenvector",
+        &graph,
+        &eigenvector,
+        (end - start).as_millis(),
+        k,
+    );
+
+    // the maximum strength bounds lambda_max from above, so alpha stays below 1 / lambda_max
+    let (lambda, source) = if eigenvector.converged {
+        (
+            rayleigh_quotient(&graph, &eigenvector.values),
+            "eigenvector",
+        )
+    } else {
+        (spectral_radius_bound(&graph), "maximum strength")
+    };
+    let alpha = 0.85 / lambda.max(1.0);
+    let start = std::time::Instant::now();
+    let result = katz_centrality(&graph, alpha, 1.0, &config);
+    let end = std::time::Instant::now();
+    println!(
+        "{} katz: lambda_max {} from the {}, alpha {}",
+        name, lambda, source, alpha
+    );
+    print_result(name, "katz", &graph, &result, (end - start).as_millis(), k);
+
+    let start = std::time::Instant::now();
+    let (hubs, authorities) = hits(&graph, &config);
+    let end = std::time::Instant::now();
+    print_result(
+        name,
+        "hits hubs",
+        &graph,
+        &hubs,
+        (end - start).as_millis(),
+        k,
+    );
+    print_top(name, "hits authorities", &graph, &authorities, k, 6);
+}
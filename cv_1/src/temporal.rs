@@ -0,0 +1,244 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+/// Undirected contact sequence: author pairs that published together in a given year.
+///
+/// Paths are time-respecting when the years along the path never decrease. Several hops inside
+/// the same year are allowed, since a year bucket stands for many papers.
+pub struct TemporalGraph {
+    // node -> contacts (year, neighbor) sorted by year
+    contacts: HashMap<usize, Vec<(u32, usize)>>,
+    min_time: u32,
+    max_time: u32,
+}
+
+/// Result of a single-source (or single-target) time-respecting search. For earliest arrival
+/// `times` holds arrival years and `links` the previous hop; for latest departure `times` holds
+/// departure years and `links` the next hop towards the target.
+pub struct TemporalSearch {
+    pub root: usize,
+    pub times: HashMap<usize, u32>,
+    pub links: HashMap<usize, usize>,
+    from_root: bool,
+}
+
+impl TemporalSearch {
+    /// Nodes with their arrival (or departure) year along the path between the root and `node`,
+    /// in travel order.
+    pub fn path_to(&self, node: usize) -> Option<Vec<(usize, u32)>> {
+        if !self.times.contains_key(&node) {
+            return None;
+        }
+        let mut path = vec![(node, self.times[&node])];
+        let mut current = node;
+        while current != self.root {
+            current = self.links[&current];
+            path.push((current, self.times[&current]));
+        }
+        if self.from_root {
+            path.reverse();
+        }
+        Some(path)
+    }
+}
+
+impl TemporalGraph {
+    pub fn new(data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>) -> Self {
+        let mut contacts: HashMap<usize, Vec<(u32, usize)>> = HashMap::new();
+        for (&year, year_data) in data.iter() {
+            for (&author1, links) in year_data.iter() {
+                for &author2 in links.keys() {
+                    if author1 == author2 {
+                        continue;
+                    }
+                    contacts.entry(author1).or_default().push((year, author2));
+                    contacts.entry(author2).or_default().push((year, author1));
+                }
+            }
+        }
+        for list in contacts.values_mut() {
+            list.sort_unstable();
+            list.dedup();
+        }
+        TemporalGraph {
+            contacts,
+            min_time: data.keys().cloned().min().unwrap_or(0),
+            max_time: data.keys().cloned().max().unwrap_or(0),
+        }
+    }
+
+    pub fn nodes(&self) -> Vec<usize> {
+        let mut nodes: Vec<usize> = self.contacts.keys().cloned().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    pub fn time_range(&self) -> (u32, u32) {
+        (self.min_time, self.max_time)
+    }
+
+    fn node_contacts(&self, node: usize) -> &[(u32, usize)] {
+        self.contacts
+            .get(&node)
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Earliest arrival year at every node reachable from `source` when leaving no earlier than
+    /// `start`.
+    pub fn earliest_arrival(&self, source: usize, start: u32) -> TemporalSearch {
+        let mut times: HashMap<usize, u32> = HashMap::new();
+        let mut predecessors: HashMap<usize, usize> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        times.insert(source, start);
+        heap.push(Reverse((start, source)));
+
+        while let Some(Reverse((arrival, node))) = heap.pop() {
+            if times[&node] < arrival {
+                continue;
+            }
+            let contacts = self.node_contacts(node);
+            let first = contacts.partition_point(|&(year, _)| year < arrival);
+            for &(year, neighbor) in &contacts[first..] {
+                if times.get(&neighbor).is_none_or(|&t| year < t) {
+                    times.insert(neighbor, year);
+                    predecessors.insert(neighbor, node);
+                    heap.push(Reverse((year, neighbor)));
+                }
+            }
+        }
+
+        TemporalSearch {
+            root: source,
+            times,
+            links: predecessors,
+            from_root: true,
+        }
+    }
+
+    /// Latest year every node can still leave and reach `target` by `deadline`.
+    pub fn latest_departure(&self, target: usize, deadline: u32) -> TemporalSearch {
+        let mut times: HashMap<usize, u32> = HashMap::new();
+        let mut successors: HashMap<usize, usize> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        times.insert(target, deadline);
+        heap.push((deadline, target));
+
+        while let Some((departure, node)) = heap.pop() {
+            if times[&node] > departure {
+                continue;
+            }
+            let contacts = self.node_contacts(node);
+            let last = contacts.partition_point(|&(year, _)| year <= departure);
+            for &(year, neighbor) in &contacts[..last] {
+                if times.get(&neighbor).is_none_or(|&t| year > t) {
+                    times.insert(neighbor, year);
+                    successors.insert(neighbor, node);
+                    heap.push((year, neighbor));
+                }
+            }
+        }
+
+        TemporalSearch {
+            root: target,
+            times,
+            links: successors,
+            from_root: false,
+        }
+    }
+
+    /// Shortest duration (in years, arrival minus departure) from `source` to every reachable
+    /// node, as `node -> (departure, arrival)`. Tries every year `source` has a contact in.
+    pub fn fastest(&self, source: usize) -> HashMap<usize, (u32, u32)> {
+        let mut departures: Vec<u32> = self.node_contacts(source).iter().map(|c| c.0).collect();
+        departures.dedup();
+
+        let mut result: HashMap<usize, (u32, u32)> = HashMap::new();
+        result.insert(source, (self.min_time, self.min_time));
+        for departure in departures.into_iter().rev() {
+            let search = self.earliest_arrival(source, departure);
+            for (&node, &arrival) in search.times.iter() {
+                if node == source {
+                    continue;
+                }
+                let better = match result.get(&node) {
+                    Some(&(d, a)) => arrival - departure < a - d,
+                    None => true,
+                };
+                if better {
+                    result.insert(node, (departure, arrival));
+                }
+            }
+        }
+        result
+    }
+
+    pub fn can_reach(&self, from: usize, to: usize, start: u32, deadline: u32) -> bool {
+        self.earliest_arrival(from, start)
+            .times
+            .get(&to)
+            .is_some_and(|&arrival| arrival <= deadline)
+    }
+
+    /// Nodes reachable from `source` over the whole time range, including `source`.
+    pub fn reachability_set(&self, source: usize) -> Vec<usize> {
+        let mut nodes: Vec<usize> = self
+            .earliest_arrival(source, self.min_time)
+            .times
+            .keys()
+            .cloned()
+            .collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    /// Harmonic temporal closeness: mean of `1 / (arrival - start + 1)` over all other nodes,
+    /// so a node reached within the start year counts fully.
+    pub fn temporal_closeness(&self, source: usize, start: u32) -> f64 {
+        let n = self.contacts.len();
+        if n < 2 {
+            return 0.0;
+        }
+        let search = self.earliest_arrival(source, start);
+        let sum: f64 = search
+            .times
+            .iter()
+            .filter(|(&node, _)| node != source)
+            .map(|(_, &arrival)| 1.0 / (arrival - start + 1) as f64)
+            .sum();
+        sum / (n - 1) as f64
+    }
+
+    pub fn temporal_closeness_par(&self, sources: &[usize], start: u32) -> Vec<(usize, f64)> {
+        sources
+            .par_iter()
+            .map(|&source| (source, self.temporal_closeness(source, start)))
+            .collect()
+    }
+}
+
+pub fn temporal_report(data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>, source: usize) {
+    let start = std::time::Instant::now();
+    let graph = TemporalGraph::new(data);
+    let (min_time, max_time) = graph.time_range();
+
+    let reachable = graph.reachability_set(source);
+    let closeness = graph.temporal_closeness(source, min_time);
+    let fastest = graph.fastest(source);
+    let max_duration = fastest.values().map(|&(d, a)| a - d).max().unwrap_or(0);
+
+    let end = std::time::Instant::now();
+    println!(
+        "Author {} ({}-{}): reaches {} authors, temporal closeness: {}, longest fastest path: {} years in {}",
+        source,
+        min_time,
+        max_time,
+        reachable.len(),
+        closeness,
+        max_duration,
+        (end - start).as_millis()
+    );
+}
@@ -0,0 +1,204 @@
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon_hash::HashMap;
+
+use crate::graph::IndexedGraph;
+use crate::link_evaluation::{
+    evaluate_candidates_by, temporal_window_split, LinkMetrics, SplitCandidates, CANDIDATE_SAMPLES,
+    SAMPLE_SEED,
+};
+use crate::link_prediction::common_neighbors;
+use crate::path_prediction::{score_path_pairs, spectral_radius_bound, PathIndex};
+
+/// How the yearly collaborations up to the training cutoff are turned into edge weights.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeWeighting {
+    Binary,
+    // Papers written together over all training years
+    Total,
+    // Number of distinct years the pair collaborated, i.e. how often the collaboration repeated
+    RepeatCount,
+    // sum_y papers_y * decay^(T - y), recent years count more
+    Recency { decay: f64 },
+}
+
+impl EdgeWeighting {
+    pub fn name(&self) -> String {
+        match self {
+            EdgeWeighting::Binary => String::from("binary"),
+            EdgeWeighting::Total => String::from("total"),
+            EdgeWeighting::RepeatCount => String::from("repeat count"),
+            EdgeWeighting::Recency { decay } => format!("recency {}", decay),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemporalScore {
+    WeightedCommonNeighbors,
+    WeightedAdamicAdar,
+    WeightedResourceAllocation,
+    // Katz truncated at length 4 over the weighted graph
+    WeightedKatz,
+}
+
+impl TemporalScore {
+    pub const ALL: [TemporalScore; 4] = [
+        TemporalScore::WeightedCommonNeighbors,
+        TemporalScore::WeightedAdamicAdar,
+        TemporalScore::WeightedResourceAllocation,
+        TemporalScore::WeightedKatz,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TemporalScore::WeightedCommonNeighbors => "weighted common neighbors",
+            TemporalScore::WeightedAdamicAdar => "weighted adamic adar",
+            TemporalScore::WeightedResourceAllocation => "weighted resource allocation",
+            TemporalScore::WeightedKatz => "weighted katz",
+        }
+    }
+}
+
+/// Weight of every author pair `(min, max)` that collaborated up to `train_until`.
+pub fn edge_weights(
+    data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>,
+    train_until: u32,
+    weighting: EdgeWeighting,
+) -> HashMap<(usize, usize), f64> {
+    let mut weights: HashMap<(usize, usize), f64> = HashMap::new();
+    for (&year, matrix) in data.iter() {
+        if year > train_until {
+            continue;
+        }
+        for (&author1, links) in matrix.iter() {
+            for (&author2, &papers) in links.iter() {
+                let value = match weighting {
+                    EdgeWeighting::Binary => 0.0,
+                    EdgeWeighting::Total => papers as f64,
+                    EdgeWeighting::RepeatCount => 1.0,
+                    EdgeWeighting::Recency { decay } => {
+                        papers as f64 * decay.powi((train_until - year) as i32)
+                    }
+                };
+                *weights
+                    .entry((author1.min(author2), author1.max(author2)))
+                    .or_insert(0.0) += value;
+            }
+        }
+    }
+    if weighting == EdgeWeighting::Binary {
+        for value in weights.values_mut() {
+            *value = 1.0;
+        }
+    }
+    weights
+}
+
+/// Copy of `graph` with the neighbor weights taken from `weights` (keyed by node ids).
+pub fn reweight(graph: &IndexedGraph, weights: &HashMap<(usize, usize), f64>) -> IndexedGraph {
+    let mut result = graph.clone();
+    for (node, neighbors) in result.neighbors.iter_mut().enumerate() {
+        let a = graph.ids[node];
+        for (neighbor, weight) in neighbors.iter_mut() {
+            let b = graph.ids[*neighbor];
+            *weight = weights.get(&(a.min(b), a.max(b))).cloned().unwrap_or(0.0);
+        }
+    }
+    result
+}
+
+fn weight(graph: &IndexedGraph, a: usize, b: usize) -> f64 {
+    let neighbors = &graph.neighbors[a];
+    neighbors
+        .binary_search_by_key(&b, |e| e.0)
+        .map_or(0.0, |i| neighbors[i].1)
+}
+
+/// Weighted neighborhood scores (Murata and Moriyasu): every common neighbor `z` contributes
+/// `w(a, z) + w(z, b)`, divided by `ln(1 + s_z)` or `s_z` for Adamic-Adar and resource
+/// allocation.
+pub fn temporal_score_pairs(
+    graph: &IndexedGraph,
+    score: TemporalScore,
+    pairs: &[(usize, usize)],
+) -> Vec<f64> {
+    if score == TemporalScore::WeightedKatz {
+        let index = PathIndex::TruncatedKatz {
+            beta: 0.5 / spectral_radius_bound(graph).max(1.0),
+            max_length: 4,
+        };
+        return score_path_pairs(graph, index, pairs);
+    }
+    pairs
+        .par_iter()
+        .map(|&(a, b)| {
+            common_neighbors(graph, a, b)
+                .into_iter()
+                .map(|z| {
+                    let w = weight(graph, a, z) + weight(graph, z, b);
+                    let s = graph.strength(z);
+                    match score {
+                        TemporalScore::WeightedCommonNeighbors => w,
+                        TemporalScore::WeightedAdamicAdar => w / (1.0 + s).ln(),
+                        TemporalScore::WeightedResourceAllocation => w / s,
+                        TemporalScore::WeightedKatz => unreachable!(),
+                    }
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Scores the candidates of a temporal split (`temporal_window_split`) with the training edges
+/// reweighted by `weights` (`edge_weights`).
+pub fn evaluate_temporal(
+    candidates: &SplitCandidates,
+    weights: &HashMap<(usize, usize), f64>,
+    score: TemporalScore,
+    k: usize,
+) -> LinkMetrics {
+    let graph = reweight(&candidates.graph, weights);
+    evaluate_candidates_by(candidates, Some(k), |_, pairs| {
+        temporal_score_pairs(&graph, score, pairs)
+    })
+}
+
+pub fn temporal_prediction_report(
+    data: &HashMap<u32, HashMap<usize, HashMap<usize, usize>>>,
+    train_until: u32,
+    horizon: u32,
+    k: usize,
+) {
+    let split = temporal_window_split(data, train_until, horizon);
+    let candidates = SplitCandidates::new(&split, CANDIDATE_SAMPLES, SAMPLE_SEED);
+    println!(
+        "Temporal link prediction: train until {}, {} new pairs in the next {} years",
+        train_until,
+        split.test.len(),
+        horizon
+    );
+    let weightings = [
+        EdgeWeighting::Binary,
+        EdgeWeighting::Total,
+        EdgeWeighting::RepeatCount,
+        EdgeWeighting::Recency { decay: 0.5 },
+        EdgeWeighting::Recency { decay: 0.8 },
+    ];
+    for weighting in weightings {
+        let weights = edge_weights(data, train_until, weighting);
+        for score in TemporalScore::ALL {
+            let start = std::time::Instant::now();
+            let metrics = evaluate_temporal(&candidates, &weights, score, k);
+            let end = std::time::Instant::now();
+            println!(
+                "{} {}: AUC {:.4}, precision@{} {:.4} in {}",
+                weighting.name(),
+                score.name(),
+                metrics.auc_roc,
+                k,
+                metrics.precision_at_k,
+                (end - start).as_millis()
+            );
+        }
+    }
+}